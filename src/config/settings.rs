@@ -1,4 +1,8 @@
+use crate::game::collision::layers::Layer;
+use crate::game::enemy::EnemyType;
+use crate::game::pickup::PickupKind;
 use config::{Config, ConfigError, Environment, File};
+use std::collections::HashMap;
 use std::env;
 
 #[derive(Debug, Deserialize)]
@@ -8,20 +12,221 @@ pub struct Window {
 }
 
 #[derive(Debug, Deserialize)]
-pub struct Point {
-    pub x: f64,
-    pub y: f64,
+pub struct Level {
+    /// Path to the Tiled map for the level, relative to the assets folder.
+    pub map: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Camera {
+    /// How many pixels on screen a single unit in the world takes up, more than 0.
+    pub zoom: f64,
+    /// How quickly the camera catches up to the player, higher is snappier.
+    pub smoothing: f64,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Player {
-    pub spawn_point: Point,
+    pub health: u32,
+    /// Damage taken from each enemy hit.
+    pub contact_damage: u32,
+    /// How long the player can't be hurt again after being hit.
+    pub invulnerability_seconds: f64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Weapon {
+    pub name: String,
+    /// Seconds between shots.
+    pub cooldown: f64,
+    /// How many shots can be fired before reloading, at least 1.
+    pub magazine_size: u32,
+    /// Seconds a reload takes.
+    pub reload_time: f64,
+    /// How many projectiles each shot fires.
+    pub projectile_count: u32,
+    /// Angle in degrees that the projectiles of a shot are spread across.
+    pub spread: f64,
+    pub projectile_speed: f64,
+    /// Damage dealt by each projectile.
+    pub damage: u32,
+    /// Automatic weapons keep firing while the mouse button is held down.
+    pub automatic: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Projectiles {
+    /// Seconds a projectile lasts before it is despawned.
+    pub max_lifetime: f64,
+    /// How far a projectile can travel from where it was fired before it is despawned.
+    pub max_distance: f64,
+}
+
+/// An entry in the drop table. Pickups are picked with a chance proportional to their weight.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Drop {
+    pub pickup: PickupKind,
+    pub weight: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Pickups {
+    /// Chance from 0.0 to 1.0 that a killed enemy drops anything at all.
+    pub drop_chance: f64,
+    pub drop_table: Vec<Drop>,
+    /// Seconds a pickup stays on the ground before disappearing.
+    pub lifetime: f64,
+    /// Health restored by a health pickup.
+    pub health: u32,
+    pub fire_rate_multiplier: f64,
+    pub speed_multiplier: f64,
+    /// Seconds the fire rate and speed boosts last.
+    pub boost_duration: f64,
+    pub invulnerability_duration: f64,
+}
+
+/// One kind of particle effect.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Emitter {
+    /// Particles let out all at once when the effect starts.
+    pub burst: u32,
+    /// Particles let out per second after the burst, for as long as the effect lasts.
+    pub rate: f64,
+    /// Seconds the effect keeps emitting at its rate.
+    pub duration: f64,
+    /// Seconds each particle lives for, picked between these two.
+    pub min_lifetime: f64,
+    pub max_lifetime: f64,
+    pub min_speed: f64,
+    pub max_speed: f64,
+    /// Width in degrees of the cone particles fly out in, centered on the direction of the event.
+    /// Effects without a direction use the whole circle.
+    pub spread: f64,
+    /// Colors a particle fades between over its life, alpha included.
+    pub start_color: [f32; 4],
+    pub end_color: [f32; 4],
+    /// Width and height of a particle at the start and end of its life.
+    pub start_size: f64,
+    pub end_size: f64,
+    /// Tinted by the particle's color. Particles without one are drawn as plain squares.
+    pub texture: Option<String>,
+}
+
+/// The particle effects played for each game event.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Particles {
+    pub fired: Emitter,
+    pub hit: Emitter,
+    pub death: Emitter,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Physics {
+    /// Length of a single simulation step in seconds, more than 0.
+    pub timestep: f64,
+    /// The most steps that will be taken in a single update when catching up on slow frames, at least 1.
+    pub max_substeps: u32,
+}
+
+/// The layers each collision layer interacts with.
+#[derive(Debug, Deserialize)]
+pub struct Collision {
+    pub player: Vec<Layer>,
+    pub enemy: Vec<Layer>,
+    pub player_projectile: Vec<Layer>,
+    pub static_world: Vec<Layer>,
+    pub pickup: Vec<Layer>,
+}
+
+/// A number of enemies of one type in a wave.
+#[derive(Clone, Debug, Deserialize)]
+pub struct WaveEnemies {
+    pub enemy: EnemyType,
+    pub count: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Wave {
+    /// The enemies in the wave. Each group is spawned in full before moving on to the next.
+    pub enemies: Vec<WaveEnemies>,
+    /// Whether the boss shows up once the rest of the wave has been spawned. Defeating it completes the wave.
+    pub boss: bool,
+    /// Seconds between each enemy being spawned.
+    pub spawn_interval: f64,
+    /// Names of the level's enemy spawn zones this wave uses. Every zone in the level is used if this is empty.
+    pub spawn_zones: Vec<String>,
+    /// Enemies will not be spawned any closer than this to the player.
+    pub min_player_distance: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Waves {
+    /// Seconds between a wave being cleared and the next one starting.
+    pub intermission: f64,
+    pub list: Vec<Wave>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Navigation {
+    /// Width and height of a single cell in the walkability grid, more than 0.
+    pub cell_size: f64,
+    /// How far away from static colliders paths are kept.
+    pub clearance: f64,
+    /// How far the player has to move before an enemy plans a new path.
+    pub replan_distance: f64,
+    /// How close an enemy has to get to a waypoint before moving on to the next one.
+    pub waypoint_radius: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Score {
+    /// Points for killing each type of enemy, before the combo multiplier.
+    pub kill_points: HashMap<EnemyType, u32>,
+    /// Seconds a hit keeps the combo going for.
+    pub combo_window: f64,
+    /// How much each hit in a combo adds to the multiplier.
+    pub combo_step: f64,
+    pub max_multiplier: f64,
+    /// Folder the high score table is saved in.
+    pub data_dir: String,
+    /// How many scores are kept in the high score table.
+    pub high_score_count: usize,
+}
+
+/// The music track looped on each screen, by asset key. The pause screen keeps playing the game's track.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Music {
+    pub main_menu: String,
+    pub playing: String,
+    pub game_over: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Audio {
+    /// Volumes from 0.0 (silent) to 1.0 (full). The master volume scales both of the others.
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub music: Music,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Settings {
     pub window: Window,
+    pub level: Level,
+    pub camera: Camera,
     pub player: Player,
+    /// The weapons the player starts with, in the order of the number keys that select them.
+    pub weapons: Vec<Weapon>,
+    pub projectiles: Projectiles,
+    pub pickups: Pickups,
+    pub particles: Particles,
+    pub physics: Physics,
+    pub collision: Collision,
+    pub waves: Waves,
+    pub navigation: Navigation,
+    pub score: Score,
+    pub audio: Audio,
 }
 
 impl Settings {
@@ -37,6 +242,44 @@ impl Settings {
 
         s.merge(Environment::with_prefix("ZOMBIES_"))?;
 
-        s.try_into()
+        let settings: Settings = s.try_into()?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Catches values that deserialize fine but would leave the game unplayable.
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.physics.timestep <= 0.0 {
+            return Err(ConfigError::Message(format!(
+                "physics timestep is {}, the world would never move forward",
+                self.physics.timestep
+            )));
+        }
+        if self.physics.max_substeps == 0 {
+            return Err(ConfigError::Message(String::from(
+                "physics max_substeps is 0, the world would never move forward",
+            )));
+        }
+        if self.navigation.cell_size <= 0.0 {
+            return Err(ConfigError::Message(format!(
+                "navigation cell_size is {}, the level can't be split into cells that small",
+                self.navigation.cell_size
+            )));
+        }
+        if self.camera.zoom <= 0.0 {
+            return Err(ConfigError::Message(format!(
+                "camera zoom is {}, nothing in the world would be visible",
+                self.camera.zoom
+            )));
+        }
+        for weapon in &self.weapons {
+            if weapon.magazine_size == 0 {
+                return Err(ConfigError::Message(format!(
+                    "weapon {} has a magazine_size of 0, it could never fire",
+                    weapon.name
+                )));
+            }
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,46 @@
+use crate::game::assets::{AssetError, Assets};
+use nalgebra::Vector2;
+use ncollide2d::shape::{Cuboid, ShapeHandle};
+use std::collections::HashMap;
+
+/// Hitboxes are the collider shapes artists draw as slices in the Aseprite exports, by the key of the texture they belong to.
+/// They are read once up front without an OpenGL context, so a headless World can use them too.
+#[derive(Clone, Default)]
+pub struct Hitboxes {
+    shapes: HashMap<String, ShapeHandle<f64>>,
+}
+
+impl Hitboxes {
+    /// Reads the hitbox out of every sprite sheet in the assets folder.
+    /// A sheet that can't be read or has no hitbox slices is reported and its texture keeps the default size.
+    pub fn load() -> Result<Hitboxes, AssetError> {
+        let mut assets = Assets::new()?;
+        let mut shapes = HashMap::new();
+        for key in assets.animated_textures()? {
+            match assets.sprite_sheet(&key) {
+                Ok(Some(sprite_sheet)) => match sprite_sheet.hitbox() {
+                    Some(hitbox) => {
+                        shapes.insert(key, hitbox);
+                    }
+                    None => println!(
+                        "The sprite sheet for {} has no hitbox slices, it keeps the default size",
+                        key
+                    ),
+                },
+                Ok(None) => {}
+                Err(error) => println!("Could not load the hitbox for {}: {}", key, error),
+            }
+        }
+        Ok(Hitboxes { shapes })
+    }
+
+    /// The hitbox for the texture at `key`. Textures without one get a box with these half extents.
+    pub fn shape_or_box(&self, key: &str, half_extents: Vector2<f64>) -> ShapeHandle<f64> {
+        self.shape(key)
+            .unwrap_or_else(|| ShapeHandle::new(Cuboid::new(half_extents)))
+    }
+
+    pub fn shape(&self, key: &str) -> Option<ShapeHandle<f64>> {
+        self.shapes.get(key).cloned()
+    }
+}
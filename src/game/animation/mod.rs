@@ -0,0 +1,291 @@
+use crate::game::animation::sheet::SpriteSheet;
+use nalgebra::Vector2;
+use std::rc::Rc;
+
+pub mod hitbox;
+pub mod sheet;
+
+// Anything moving slower than this is standing still as far as animations are concerned
+const WALK_SPEED_THRESHOLD: f64 = 5.0;
+
+/// What an entity is doing, each state plays the clip with the same name from the entity's sprite sheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationState {
+    Idle,
+    Walk,
+    Hit,
+    Die,
+}
+
+impl AnimationState {
+    pub fn clip_name(self) -> &'static str {
+        match self {
+            AnimationState::Idle => "idle",
+            AnimationState::Walk => "walk",
+            AnimationState::Hit => "hit",
+            AnimationState::Die => "die",
+        }
+    }
+
+    /// Looping clips play until something else is played. The rest play once and hold their last frame.
+    fn loops(self) -> bool {
+        match self {
+            AnimationState::Idle | AnimationState::Walk => true,
+            AnimationState::Hit | AnimationState::Die => false,
+        }
+    }
+
+    /// Walk while moving and idle otherwise.
+    pub fn from_velocity(velocity: Vector2<f64>) -> AnimationState {
+        if velocity.norm() > WALK_SPEED_THRESHOLD {
+            AnimationState::Walk
+        } else {
+            AnimationState::Idle
+        }
+    }
+}
+
+/// Animator plays the clips from a sprite sheet.
+/// Clips that only play once are not cut short by looping ones, so a hit is seen through before going back to walking,
+/// and nothing takes over once the die clip has started.
+pub struct Animator {
+    sheet: Rc<SpriteSheet>,
+    state: AnimationState,
+    // Index into the current clip
+    position: usize,
+    time_in_frame: f64,
+    finished: bool,
+}
+
+impl Animator {
+    pub fn new(sheet: Rc<SpriteSheet>) -> Animator {
+        Animator {
+            sheet,
+            state: AnimationState::Idle,
+            position: 0,
+            time_in_frame: 0.0,
+            finished: false,
+        }
+    }
+
+    pub fn sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+
+    pub fn has_clip(&self, state: AnimationState) -> bool {
+        self.sheet.clip(state.clip_name()).is_some()
+    }
+
+    /// Switches to the clip for `state`, starting it from the beginning.
+    /// Playing the looping clip that is already playing carries on where it was.
+    pub fn play(&mut self, state: AnimationState) {
+        if state == self.state && state.loops() {
+            return;
+        }
+        if self.state == AnimationState::Die {
+            return;
+        }
+        if !self.state.loops() && !self.finished && state.loops() {
+            return;
+        }
+        self.state = state;
+        self.position = 0;
+        self.time_in_frame = 0.0;
+        self.finished = false;
+    }
+
+    /// Moves the clip forward by `dt` seconds.
+    /// Frames with no duration are passed straight over, so a clip that only plays once still finishes when its last frame has none.
+    pub fn update(&mut self, dt: f64) {
+        if self.finished {
+            return;
+        }
+        self.time_in_frame += dt;
+        // Counts the frames with no duration passed in a row, a looping clip made of nothing else would go round forever
+        let mut instant_frames = 0;
+        loop {
+            let (length, duration) = {
+                let clip = self.clip();
+                (clip.len(), self.sheet.frame(clip[self.position]).duration)
+            };
+            if duration > 0.0 {
+                if self.time_in_frame < duration {
+                    return;
+                }
+                self.time_in_frame -= duration;
+                instant_frames = 0;
+            } else {
+                instant_frames += 1;
+                if instant_frames > length {
+                    return;
+                }
+            }
+            if self.position + 1 < length {
+                self.position += 1;
+            } else if self.state.loops() {
+                self.position = 0;
+            } else {
+                self.finished = true;
+                return;
+            }
+        }
+    }
+
+    /// True once a clip that only plays once has shown its last frame.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The part of the sheet image to draw for the current frame, as [x, y, width, height].
+    pub fn src_rect(&self) -> [f64; 4] {
+        self.sheet.frame(self.clip()[self.position]).src_rect
+    }
+
+    /// The frames for the current state. Sheets without a clip for it fall back to idle, and then to their whole timeline.
+    fn clip(&self) -> &[usize] {
+        self.sheet
+            .clip(self.state.clip_name())
+            .or_else(|| self.sheet.clip(AnimationState::Idle.clip_name()))
+            .unwrap_or_else(|| self.sheet.timeline())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    // A sheet of 32 pixel wide frames with these durations in milliseconds, and these (name, from, to) tags played forward
+    fn sheet(durations: &[u32], tags: &[(&str, usize, usize)]) -> Rc<SpriteSheet> {
+        let frames: Vec<String> = durations
+            .iter()
+            .enumerate()
+            .map(|(index, duration)| {
+                format!(
+                    r#"{{ "frame": {{ "x": {}, "y": 0, "w": 32, "h": 32 }}, "sourceSize": {{ "w": 32, "h": 32 }}, "duration": {} }}"#,
+                    index * 32,
+                    duration
+                )
+            })
+            .collect();
+        let tags: Vec<String> = tags
+            .iter()
+            .map(|(name, from, to)| {
+                format!(
+                    r#"{{ "name": "{}", "from": {}, "to": {}, "direction": "forward" }}"#,
+                    name, from, to
+                )
+            })
+            .collect();
+        let json = format!(
+            r#"{{ "frames": [{}], "meta": {{ "image": "test.png", "frameTags": [{}] }} }}"#,
+            frames.join(", "),
+            tags.join(", ")
+        );
+        Rc::new(SpriteSheet::from_json(&json, Path::new("")).unwrap())
+    }
+
+    // Which frame of the sheet is being shown
+    fn frame(animator: &Animator) -> usize {
+        (animator.src_rect()[0] / 32.0) as usize
+    }
+
+    fn animator() -> Animator {
+        Animator::new(sheet(
+            &[100, 100, 100, 100, 100, 100],
+            &[("idle", 0, 1), ("walk", 2, 3), ("hit", 4, 5), ("die", 4, 5)],
+        ))
+    }
+
+    #[test]
+    fn looping_clips_go_back_to_their_start() {
+        let mut animator = animator();
+        animator.play(AnimationState::Walk);
+        assert_eq!(frame(&animator), 2);
+        animator.update(0.1);
+        assert_eq!(frame(&animator), 3);
+        animator.update(0.1);
+        assert_eq!(frame(&animator), 2);
+        assert!(!animator.is_finished());
+    }
+
+    #[test]
+    fn a_long_update_moves_through_several_frames() {
+        let mut animator = animator();
+        animator.play(AnimationState::Walk);
+        animator.update(0.35);
+        assert_eq!(frame(&animator), 3);
+    }
+
+    #[test]
+    fn one_shot_clips_hold_their_last_frame() {
+        let mut animator = animator();
+        animator.play(AnimationState::Hit);
+        animator.update(0.1);
+        assert!(!animator.is_finished());
+        animator.update(1.0);
+        assert!(animator.is_finished());
+        assert_eq!(frame(&animator), 5);
+    }
+
+    #[test]
+    fn a_hit_is_seen_through_before_walking_again() {
+        let mut animator = animator();
+        animator.play(AnimationState::Hit);
+        animator.play(AnimationState::Walk);
+        assert_eq!(frame(&animator), 4);
+        animator.update(0.2);
+        animator.play(AnimationState::Walk);
+        assert_eq!(frame(&animator), 2);
+    }
+
+    #[test]
+    fn nothing_takes_over_from_dying() {
+        let mut animator = animator();
+        animator.play(AnimationState::Die);
+        animator.update(0.1);
+        animator.play(AnimationState::Hit);
+        animator.play(AnimationState::Idle);
+        assert_eq!(frame(&animator), 5);
+    }
+
+    #[test]
+    fn a_zero_length_last_frame_still_finishes_the_clip() {
+        let mut animator = Animator::new(sheet(&[100, 100, 0], &[("die", 1, 2)]));
+        animator.play(AnimationState::Die);
+        animator.update(0.1);
+        assert!(animator.is_finished());
+        assert_eq!(frame(&animator), 2);
+    }
+
+    #[test]
+    fn a_looping_clip_of_zero_length_frames_does_not_hang() {
+        let mut animator = Animator::new(sheet(&[0, 0], &[("idle", 0, 1)]));
+        animator.update(1.0);
+        assert!(!animator.is_finished());
+    }
+
+    #[test]
+    fn missing_clips_fall_back_to_idle_and_then_the_timeline() {
+        let mut with_idle = Animator::new(sheet(&[100, 100, 100], &[("idle", 1, 2)]));
+        with_idle.play(AnimationState::Walk);
+        assert_eq!(frame(&with_idle), 1);
+        assert!(!with_idle.has_clip(AnimationState::Walk));
+
+        let mut untagged = Animator::new(sheet(&[100, 100, 100], &[]));
+        untagged.update(0.25);
+        assert_eq!(frame(&untagged), 2);
+    }
+
+    #[test]
+    fn moving_is_walking() {
+        assert_eq!(
+            AnimationState::from_velocity(Vector2::new(0.0, 1.0)),
+            AnimationState::Idle
+        );
+        assert_eq!(
+            AnimationState::from_velocity(Vector2::new(30.0, 40.0)),
+            AnimationState::Walk
+        );
+    }
+}
@@ -0,0 +1,335 @@
+use nalgebra::{Isometry2, Vector2};
+use ncollide2d::shape::{Compound, Cuboid, ShapeHandle};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+
+/// The parts of an Aseprite JSON export the game uses.
+/// Sheets are exported with `aseprite -b <file>.aseprite --sheet <file>.png --data <file>.json --format json-array --list-tags --list-slices`,
+/// the array format keeps the frames in timeline order. Without `--list-slices` the hitboxes are left out of the export.
+#[derive(Deserialize)]
+struct AsepriteExport {
+    frames: Vec<AsepriteFrame>,
+    meta: AsepriteMeta,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsepriteFrame {
+    frame: AsepriteRect,
+    /// The size of the frame before it was trimmed. This is the size the sprite is drawn at.
+    source_size: AsepriteSize,
+    /// Milliseconds the frame is shown for.
+    duration: u32,
+}
+
+#[derive(Deserialize)]
+struct AsepriteSize {
+    w: f64,
+    h: f64,
+}
+
+#[derive(Deserialize)]
+struct AsepriteRect {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsepriteMeta {
+    /// The sheet image, relative to the JSON file.
+    image: String,
+    // Only exported when --list-tags is passed
+    #[serde(default)]
+    frame_tags: Vec<FrameTag>,
+    // Only exported when --list-slices is passed
+    #[serde(default)]
+    slices: Vec<Slice>,
+}
+
+/// A named rectangle drawn over the sprite in Aseprite.
+#[derive(Deserialize)]
+struct Slice {
+    name: String,
+    /// Where the slice is on each frame it changes on. Hitboxes only use the first.
+    keys: Vec<SliceKey>,
+}
+
+#[derive(Deserialize)]
+struct SliceKey {
+    /// Relative to the top left corner of the frame.
+    bounds: AsepriteRect,
+}
+
+#[derive(Deserialize)]
+struct FrameTag {
+    name: String,
+    from: usize,
+    to: usize,
+    direction: Direction,
+}
+
+/// The order a tag's frames are played in.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(try_from = "String")]
+enum Direction {
+    Forward,
+    Reverse,
+    PingPong,
+}
+
+impl TryFrom<String> for Direction {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        match name.as_str() {
+            "forward" => Ok(Direction::Forward),
+            "reverse" => Ok(Direction::Reverse),
+            "pingpong" => Ok(Direction::PingPong),
+            _ => Err(format!("unknown tag direction {}", name)),
+        }
+    }
+}
+
+/// A single frame of a sprite sheet.
+#[derive(Clone, Copy, Debug)]
+pub struct Frame {
+    /// The frame's rectangle in the sheet image as [x, y, width, height].
+    pub src_rect: [f64; 4],
+    /// Seconds the frame is shown for.
+    pub duration: f64,
+}
+
+// Slices whose names start with this make up the hitbox, so several can be combined into one shape
+const HITBOX_SLICE_PREFIX: &str = "hitbox";
+
+/// SpriteSheet is an Aseprite export: one image with every frame on it, and named clips made out of those frames.
+/// It only refers to its image by asset key so it can be loaded without an OpenGL context.
+pub struct SpriteSheet {
+    image: String,
+    frames: Vec<Frame>,
+    // The frames in each tag in the order they are played, by tag name
+    clips: HashMap<String, Vec<usize>>,
+    // Every frame in timeline order, played when the sheet has no tags
+    timeline: Vec<usize>,
+    // Built out of the hitbox slices, if there are any
+    hitbox: Option<ShapeHandle<f64>>,
+}
+
+impl SpriteSheet {
+    /// Reads an Aseprite JSON export. `directory` is where the JSON was found relative to the assets folder,
+    /// the sheet's image key is resolved against it.
+    pub fn from_json(json: &str, directory: &Path) -> Result<SpriteSheet, String> {
+        let export: AsepriteExport =
+            serde_json::from_str(json).map_err(|error| error.to_string())?;
+        if export.frames.is_empty() {
+            return Err("the sheet has no frames".to_string());
+        }
+        let frames: Vec<Frame> = export
+            .frames
+            .iter()
+            .map(|frame| Frame {
+                src_rect: [frame.frame.x, frame.frame.y, frame.frame.w, frame.frame.h],
+                duration: f64::from(frame.duration) / 1000.0,
+            })
+            .collect();
+
+        let mut clips = HashMap::new();
+        for tag in export.meta.frame_tags {
+            if tag.from > tag.to || tag.to >= frames.len() {
+                return Err(format!(
+                    "tag {} runs from frame {} to {} but the sheet has {} frames",
+                    tag.name,
+                    tag.from,
+                    tag.to,
+                    frames.len()
+                ));
+            }
+            let forward: Vec<usize> = (tag.from..=tag.to).collect();
+            let clip = match tag.direction {
+                Direction::Forward => forward,
+                Direction::Reverse => forward.into_iter().rev().collect(),
+                // Back down again without repeating either end, so looping doesn't hold on them
+                Direction::PingPong => {
+                    let back = (tag.from + 1..tag.to).rev();
+                    forward.into_iter().chain(back).collect()
+                }
+            };
+            clips.insert(tag.name, clip);
+        }
+
+        let hitbox = SpriteSheet::hitbox_from_slices(&export.meta.slices, &export.frames[0]);
+
+        Ok(SpriteSheet {
+            image: directory
+                .join(&export.meta.image)
+                .to_string_lossy()
+                .into_owned(),
+            timeline: (0..frames.len()).collect(),
+            frames,
+            clips,
+            hitbox,
+        })
+    }
+
+    /// Turns every hitbox slice into a box and sticks them together into one compound shape.
+    /// Sprites are drawn centered on their body, so the boxes are placed relative to the middle of the frame.
+    fn hitbox_from_slices(slices: &[Slice], frame: &AsepriteFrame) -> Option<ShapeHandle<f64>> {
+        let center = Vector2::new(frame.source_size.w, frame.source_size.h) / 2.0;
+        let parts: Vec<(Isometry2<f64>, ShapeHandle<f64>)> = slices
+            .iter()
+            .filter(|slice| slice.name.starts_with(HITBOX_SLICE_PREFIX))
+            .filter_map(|slice| slice.keys.first())
+            .map(|key| {
+                let half_extents = Vector2::new(key.bounds.w, key.bounds.h) / 2.0;
+                let offset = Vector2::new(key.bounds.x, key.bounds.y) + half_extents - center;
+                (
+                    Isometry2::translation(offset[0], offset[1]),
+                    ShapeHandle::new(Cuboid::new(half_extents)),
+                )
+            })
+            .collect();
+        if parts.is_empty() {
+            None
+        } else {
+            Some(ShapeHandle::new(Compound::new(parts)))
+        }
+    }
+
+    /// The asset key of the sheet's image.
+    pub fn image(&self) -> &str {
+        &self.image
+    }
+
+    pub fn frame(&self, index: usize) -> &Frame {
+        &self.frames[index]
+    }
+
+    /// The frames of the clip with this name, in the order they are played.
+    pub fn clip(&self, name: &str) -> Option<&[usize]> {
+        self.clips.get(name).map(|clip| clip.as_slice())
+    }
+
+    /// Every frame in the sheet in timeline order.
+    pub fn timeline(&self) -> &[usize] {
+        &self.timeline
+    }
+
+    /// The collider shape drawn as slices over the art, if the export has any.
+    pub fn hitbox(&self) -> Option<ShapeHandle<f64>> {
+        self.hitbox.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Four 32x32 frames side by side, shown for 100ms each
+    fn export(tags: &str, slices: &str) -> String {
+        let frames: Vec<String> = (0..4)
+            .map(|index| {
+                format!(
+                    r#"{{ "frame": {{ "x": {}, "y": 0, "w": 32, "h": 32 }}, "sourceSize": {{ "w": 32, "h": 32 }}, "duration": 100 }}"#,
+                    index * 32
+                )
+            })
+            .collect();
+        format!(
+            r#"{{ "frames": [{}], "meta": {{ "image": "test.png", "frameTags": [{}], "slices": [{}] }} }}"#,
+            frames.join(", "),
+            tags,
+            slices
+        )
+    }
+
+    fn tag(name: &str, from: usize, to: usize, direction: &str) -> String {
+        format!(
+            r#"{{ "name": "{}", "from": {}, "to": {}, "direction": "{}" }}"#,
+            name, from, to, direction
+        )
+    }
+
+    fn slice(name: &str, bounds: [u32; 4]) -> String {
+        format!(
+            r#"{{ "name": "{}", "keys": [{{ "frame": 0, "bounds": {{ "x": {}, "y": {}, "w": {}, "h": {} }} }}] }}"#,
+            name, bounds[0], bounds[1], bounds[2], bounds[3]
+        )
+    }
+
+    fn load(json: &str) -> SpriteSheet {
+        SpriteSheet::from_json(json, Path::new("animations")).unwrap()
+    }
+
+    #[test]
+    fn tags_play_in_their_direction() {
+        let tags = [
+            tag("forward", 0, 3, "forward"),
+            tag("reverse", 0, 3, "reverse"),
+            tag("pingpong", 0, 3, "pingpong"),
+        ];
+        let sheet = load(&export(&tags.join(", "), ""));
+        assert_eq!(sheet.clip("forward"), Some(&[0, 1, 2, 3][..]));
+        assert_eq!(sheet.clip("reverse"), Some(&[3, 2, 1, 0][..]));
+        assert_eq!(sheet.clip("pingpong"), Some(&[0, 1, 2, 3, 2, 1][..]));
+        assert_eq!(sheet.clip("missing"), None);
+        assert_eq!(sheet.timeline(), &[0, 1, 2, 3][..]);
+    }
+
+    #[test]
+    fn a_two_frame_pingpong_does_not_repeat_either_end() {
+        let sheet = load(&export(&tag("idle", 1, 2, "pingpong"), ""));
+        assert_eq!(sheet.clip("idle"), Some(&[1, 2][..]));
+    }
+
+    #[test]
+    fn frames_are_read_in_seconds_and_the_image_is_next_to_the_json() {
+        let sheet = load(&export("", ""));
+        let frame = sheet.frame(2);
+        assert_eq!(frame.src_rect, [64.0, 0.0, 32.0, 32.0]);
+        assert!((frame.duration - 0.1).abs() < 1.0e-9);
+        assert_eq!(Path::new(sheet.image()), Path::new("animations/test.png"));
+    }
+
+    #[test]
+    fn bad_exports_are_rejected() {
+        let past_the_end = export(&tag("walk", 2, 4, "forward"), "");
+        assert!(SpriteSheet::from_json(&past_the_end, Path::new("")).is_err());
+        let backwards = export(&tag("walk", 3, 1, "forward"), "");
+        assert!(SpriteSheet::from_json(&backwards, Path::new("")).is_err());
+        let unknown_direction = export(&tag("walk", 0, 1, "sideways"), "");
+        assert!(SpriteSheet::from_json(&unknown_direction, Path::new("")).is_err());
+        let no_frames = r#"{ "frames": [], "meta": { "image": "test.png" } }"#;
+        assert!(SpriteSheet::from_json(no_frames, Path::new("")).is_err());
+    }
+
+    #[test]
+    fn hitbox_slices_are_placed_around_the_middle_of_the_frame() {
+        let slices = [
+            slice("hitbox-head", [0, 0, 10, 10]),
+            slice("hitbox-body", [16, 16, 16, 8]),
+            slice("pivot", [0, 0, 32, 32]),
+        ];
+        let sheet = load(&export("", &slices.join(", ")));
+        let hitbox = sheet.hitbox().unwrap();
+        let compound = hitbox.as_shape::<Compound<f64>>().unwrap();
+        let offsets: Vec<Vector2<f64>> = compound
+            .shapes()
+            .iter()
+            .map(|(position, _)| position.translation.vector)
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![Vector2::new(-11.0, -11.0), Vector2::new(8.0, 4.0)]
+        );
+    }
+
+    #[test]
+    fn sheets_without_hitbox_slices_have_no_hitbox() {
+        let sheet = load(&export("", &slice("pivot", [0, 0, 32, 32])));
+        assert!(sheet.hitbox().is_none());
+    }
+}
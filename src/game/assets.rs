@@ -0,0 +1,174 @@
+use crate::game::animation::sheet::SpriteSheet;
+use opengl_graphics::{GlyphCache, Texture, TextureSettings};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+// Where Aseprite exports are kept, relative to the assets folder
+const ANIMATIONS_FOLDER: &str = "animations";
+
+#[derive(Debug)]
+pub enum AssetError {
+    /// The assets folder could not be found near the working directory.
+    AssetsFolderNotFound,
+    /// There is no file for the requested asset.
+    MissingFile(PathBuf),
+    /// The file exists but could not be turned into a texture.
+    InvalidTexture { path: PathBuf, reason: String },
+    /// The file exists but could not be loaded as a font.
+    InvalidFont { path: PathBuf, reason: String },
+    /// The file exists but is not a usable Aseprite JSON export.
+    InvalidSpriteSheet { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetError::AssetsFolderNotFound => write!(f, "could not find the assets folder"),
+            AssetError::MissingFile(path) => write!(f, "missing asset {}", path.display()),
+            AssetError::InvalidTexture { path, reason } => {
+                write!(f, "could not load texture {}: {}", path.display(), reason)
+            }
+            AssetError::InvalidFont { path, reason } => {
+                write!(f, "could not load font {}: {}", path.display(), reason)
+            }
+            AssetError::InvalidSpriteSheet { path, reason } => write!(
+                f,
+                "could not load sprite sheet {}: {}",
+                path.display(),
+                reason
+            ),
+        }
+    }
+}
+
+impl Error for AssetError {}
+
+/// Assets is the registry for everything loaded out of the assets folder.
+/// The folder is only searched for once and every texture is only read from disk the first time it is asked for.
+/// After that the same texture is shared between every sprite that uses it.
+pub struct Assets {
+    directory: PathBuf,
+    textures: HashMap<String, Rc<Texture>>,
+    // Sprite sheets by the key of the texture they animate, None for textures without one
+    sprite_sheets: HashMap<String, Option<Rc<SpriteSheet>>>,
+}
+
+impl Assets {
+    pub fn new() -> Result<Assets, AssetError> {
+        let directory = find_folder::Search::ParentsThenKids(3, 3)
+            .for_folder("assets")
+            .map_err(|_| AssetError::AssetsFolderNotFound)?;
+
+        Ok(Assets {
+            directory,
+            textures: HashMap::new(),
+            sprite_sheets: HashMap::new(),
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Gets the texture stored at `key`, which is its path relative to the assets folder.
+    /// Loading a texture needs an OpenGL context.
+    pub fn texture(&mut self, key: &str) -> Result<Rc<Texture>, AssetError> {
+        if let Some(texture) = self.textures.get(key) {
+            return Ok(Rc::clone(texture));
+        }
+
+        let path = self.directory.join(key);
+        if !path.is_file() {
+            return Err(AssetError::MissingFile(path));
+        }
+        let texture = Texture::from_path(&path, &TextureSettings::new())
+            .map_err(|reason| AssetError::InvalidTexture { path, reason })?;
+
+        let texture = Rc::new(texture);
+        self.textures.insert(key.to_string(), Rc::clone(&texture));
+        Ok(texture)
+    }
+
+    /// Gets the sprite sheet that animates the texture stored at `key`, if there is one.
+    /// Aseprite exports live in the animations folder under the same path as the texture, so `baby.png` is animated by `animations/baby.json`.
+    pub fn sprite_sheet(&mut self, key: &str) -> Result<Option<Rc<SpriteSheet>>, AssetError> {
+        if let Some(sprite_sheet) = self.sprite_sheets.get(key) {
+            return Ok(sprite_sheet.clone());
+        }
+
+        let sheet_key = Path::new(ANIMATIONS_FOLDER).join(Path::new(key).with_extension("json"));
+        let path = self.directory.join(&sheet_key);
+        let sprite_sheet = if path.is_file() {
+            let json =
+                fs::read_to_string(&path).map_err(|error| AssetError::InvalidSpriteSheet {
+                    path: path.clone(),
+                    reason: error.to_string(),
+                })?;
+            let directory = sheet_key.parent().unwrap_or_else(|| Path::new(""));
+            let sprite_sheet = SpriteSheet::from_json(&json, directory)
+                .map_err(|reason| AssetError::InvalidSpriteSheet { path, reason })?;
+            Some(Rc::new(sprite_sheet))
+        } else {
+            None
+        };
+
+        self.sprite_sheets
+            .insert(key.to_string(), sprite_sheet.clone());
+        Ok(sprite_sheet)
+    }
+
+    /// The keys of every texture with a sprite sheet in the animations folder.
+    pub fn animated_textures(&self) -> Result<Vec<String>, AssetError> {
+        let mut keys = vec![];
+        let animations = self.directory.join(ANIMATIONS_FOLDER);
+        if animations.is_dir() {
+            Assets::find_sprite_sheets(&animations, Path::new(""), &mut keys)?;
+        }
+        Ok(keys)
+    }
+
+    fn find_sprite_sheets(
+        directory: &Path,
+        relative: &Path,
+        keys: &mut Vec<String>,
+    ) -> Result<(), AssetError> {
+        let entries = fs::read_dir(directory)
+            .map_err(|_| AssetError::MissingFile(directory.to_path_buf()))?;
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let relative = relative.join(entry.file_name());
+            if path.is_dir() {
+                Assets::find_sprite_sheets(&path, &relative, keys)?;
+            } else if path
+                .extension()
+                .map_or(false, |extension| extension == "json")
+            {
+                keys.push(
+                    relative
+                        .with_extension("png")
+                        .to_string_lossy()
+                        .into_owned(),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the font stored at `key` into a glyph cache for drawing text.
+    pub fn glyphs(&self, key: &str) -> Result<GlyphCache<'static>, AssetError> {
+        let path = self.directory.join(key);
+        if !path.is_file() {
+            return Err(AssetError::MissingFile(path));
+        }
+        GlyphCache::new(&path, (), TextureSettings::new()).map_err(|error| {
+            AssetError::InvalidFont {
+                path,
+                reason: error.to_string(),
+            }
+        })
+    }
+}
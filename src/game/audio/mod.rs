@@ -0,0 +1,216 @@
+use crate::config::settings::{Audio as AudioSettings, Music};
+use crate::game::state::GameState;
+
+#[cfg(feature = "sound")]
+pub mod rodio_backend;
+
+/// AudioBackend is whatever actually makes the sound. Sounds and music are referred to by their path relative to the assets folder.
+pub trait AudioBackend {
+    /// Plays the sound stored at `key` once. `volume` is from 0.0 (silent) to 1.0 (full).
+    fn play_sound(&mut self, key: &str, volume: f32);
+
+    /// Starts looping the track stored at `key`, replacing whatever music was playing.
+    fn play_music(&mut self, key: &str, volume: f32);
+
+    fn stop_music(&mut self);
+}
+
+/// A backend that plays nothing, for headless runs, builds without the `sound` feature and machines without a sound device.
+#[derive(Default)]
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+    fn play_sound(&mut self, _key: &str, _volume: f32) {}
+
+    fn play_music(&mut self, _key: &str, _volume: f32) {}
+
+    fn stop_music(&mut self) {}
+}
+
+/// One-shot sounds played on game events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoundEffect {
+    Shoot,
+    Hit,
+    BabyCry,
+    PlayerHurt,
+    Pickup,
+}
+
+impl SoundEffect {
+    pub fn key(self) -> &'static str {
+        match self {
+            SoundEffect::Shoot => "sounds/shoot.wav",
+            SoundEffect::Hit => "sounds/hit.wav",
+            SoundEffect::BabyCry => "sounds/baby_cry.wav",
+            SoundEffect::PlayerHurt => "sounds/player_hurt.wav",
+            SoundEffect::Pickup => "sounds/pickup.wav",
+        }
+    }
+}
+
+/// Audio plays sound effects and the music for each screen through a backend, at the volumes from the config.
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    music_volume: f32,
+    sfx_volume: f32,
+    music: Music,
+    // The track that is looping right now, so changing to a screen with the same track doesn't restart it
+    current_track: Option<String>,
+}
+
+impl Audio {
+    pub fn new(config: &AudioSettings, backend: Box<dyn AudioBackend>) -> Audio {
+        let master_volume = volume(config.master_volume);
+        Audio {
+            backend,
+            music_volume: master_volume * volume(config.music_volume),
+            sfx_volume: master_volume * volume(config.sfx_volume),
+            music: config.music.clone(),
+            current_track: None,
+        }
+    }
+
+    /// Swaps the backend, carrying the music that was playing over to the new one.
+    pub fn set_backend(&mut self, backend: Box<dyn AudioBackend>) {
+        self.backend.stop_music();
+        self.backend = backend;
+        if let Some(track) = &self.current_track {
+            self.backend.play_music(track, self.music_volume);
+        }
+    }
+
+    pub fn play(&mut self, effect: SoundEffect) {
+        self.backend.play_sound(effect.key(), self.sfx_volume);
+    }
+
+    /// Loops the track for `state`, unless it is already playing.
+    pub fn play_music_for(&mut self, state: GameState) {
+        let track = match state {
+            GameState::MainMenu => &self.music.main_menu,
+            GameState::Playing | GameState::Paused => &self.music.playing,
+            GameState::GameOver => &self.music.game_over,
+        };
+        if self.current_track.as_ref() == Some(track) {
+            return;
+        }
+        self.backend.play_music(track, self.music_volume);
+        self.current_track = Some(track.clone());
+    }
+}
+
+fn volume(volume: f32) -> f32 {
+    volume.max(0.0).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Debug, PartialEq)]
+    enum Call {
+        Sound(String, f32),
+        Music(String, f32),
+        StopMusic,
+    }
+
+    // Shares its log so the calls can still be looked at once Audio owns the backend
+    #[derive(Clone, Default)]
+    struct RecordingBackend {
+        calls: Rc<RefCell<Vec<Call>>>,
+    }
+
+    impl AudioBackend for RecordingBackend {
+        fn play_sound(&mut self, key: &str, volume: f32) {
+            self.calls
+                .borrow_mut()
+                .push(Call::Sound(key.to_string(), volume));
+        }
+
+        fn play_music(&mut self, key: &str, volume: f32) {
+            self.calls
+                .borrow_mut()
+                .push(Call::Music(key.to_string(), volume));
+        }
+
+        fn stop_music(&mut self) {
+            self.calls.borrow_mut().push(Call::StopMusic);
+        }
+    }
+
+    fn settings(master_volume: f32, music_volume: f32, sfx_volume: f32) -> AudioSettings {
+        AudioSettings {
+            master_volume,
+            music_volume,
+            sfx_volume,
+            music: Music {
+                main_menu: String::from("music/menu.wav"),
+                playing: String::from("music/playing.wav"),
+                game_over: String::from("music/game_over.wav"),
+            },
+        }
+    }
+
+    #[test]
+    fn screens_with_the_same_track_do_not_restart_it() {
+        let backend = RecordingBackend::default();
+        let mut audio = Audio::new(&settings(1.0, 1.0, 1.0), Box::new(backend.clone()));
+        audio.play_music_for(GameState::Playing);
+        audio.play_music_for(GameState::Paused);
+        audio.play_music_for(GameState::Playing);
+        audio.play_music_for(GameState::GameOver);
+        assert_eq!(
+            *backend.calls.borrow(),
+            vec![
+                Call::Music(String::from("music/playing.wav"), 1.0),
+                Call::Music(String::from("music/game_over.wav"), 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn volumes_are_scaled_by_the_master_volume() {
+        let backend = RecordingBackend::default();
+        let mut audio = Audio::new(&settings(0.5, 0.5, 0.25), Box::new(backend.clone()));
+        audio.play_music_for(GameState::MainMenu);
+        audio.play(SoundEffect::Shoot);
+        assert_eq!(
+            *backend.calls.borrow(),
+            vec![
+                Call::Music(String::from("music/menu.wav"), 0.25),
+                Call::Sound(String::from("sounds/shoot.wav"), 0.125),
+            ]
+        );
+    }
+
+    #[test]
+    fn volumes_are_clamped() {
+        let backend = RecordingBackend::default();
+        let mut audio = Audio::new(&settings(2.0, 1.5, -1.0), Box::new(backend.clone()));
+        audio.play_music_for(GameState::MainMenu);
+        audio.play(SoundEffect::Hit);
+        assert_eq!(
+            *backend.calls.borrow(),
+            vec![
+                Call::Music(String::from("music/menu.wav"), 1.0),
+                Call::Sound(String::from("sounds/hit.wav"), 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_new_backend_picks_up_the_music_that_was_playing() {
+        let old_backend = RecordingBackend::default();
+        let new_backend = RecordingBackend::default();
+        let mut audio = Audio::new(&settings(1.0, 1.0, 1.0), Box::new(old_backend.clone()));
+        audio.play_music_for(GameState::MainMenu);
+        audio.set_backend(Box::new(new_backend.clone()));
+        assert_eq!(old_backend.calls.borrow().last(), Some(&Call::StopMusic));
+        assert_eq!(
+            *new_backend.calls.borrow(),
+            vec![Call::Music(String::from("music/menu.wav"), 1.0)]
+        );
+    }
+}
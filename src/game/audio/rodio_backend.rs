@@ -0,0 +1,112 @@
+use crate::game::assets::{AssetError, Assets};
+use crate::game::audio::AudioBackend;
+use rodio::{Decoder, Device, Sink, Source};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum AudioError {
+    Assets(AssetError),
+    /// The system has nothing to play sound on.
+    NoOutputDevice,
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AudioError::Assets(error) => write!(f, "{}", error),
+            AudioError::NoOutputDevice => write!(f, "no audio output device"),
+        }
+    }
+}
+
+impl Error for AudioError {}
+
+impl From<AssetError> for AudioError {
+    fn from(error: AssetError) -> AudioError {
+        AudioError::Assets(error)
+    }
+}
+
+/// RodioBackend plays sound on the system's default output device.
+/// Sound files are read from the assets folder the first time they are played and kept in memory after that.
+pub struct RodioBackend {
+    device: Device,
+    directory: PathBuf,
+    // The contents of every sound file that has been played by asset key, None for ones that can't be played
+    sounds: HashMap<String, Option<Vec<u8>>>,
+    // Only present while a track is looping
+    music: Option<Sink>,
+}
+
+impl RodioBackend {
+    pub fn new() -> Result<RodioBackend, AudioError> {
+        let directory = Assets::new()?.directory().to_path_buf();
+        let device = rodio::default_output_device().ok_or(AudioError::NoOutputDevice)?;
+        Ok(RodioBackend {
+            device,
+            directory,
+            sounds: HashMap::new(),
+            music: None,
+        })
+    }
+
+    /// Decodes the sound stored at `key`. Files that can't be read or decoded are reported the first time and skipped after that.
+    fn decode(&mut self, key: &str) -> Option<Decoder<Cursor<Vec<u8>>>> {
+        let directory = &self.directory;
+        let bytes = self
+            .sounds
+            .entry(key.to_string())
+            .or_insert_with(|| {
+                let path = directory.join(key);
+                match fs::read(&path) {
+                    Ok(bytes) => Some(bytes),
+                    Err(error) => {
+                        println!("Could not read sound {}: {}", path.display(), error);
+                        None
+                    }
+                }
+            })
+            .clone()?;
+        match Decoder::new(Cursor::new(bytes)) {
+            Ok(decoder) => Some(decoder),
+            Err(error) => {
+                println!("Could not decode sound {}: {}", key, error);
+                self.sounds.insert(key.to_string(), None);
+                None
+            }
+        }
+    }
+}
+
+impl AudioBackend for RodioBackend {
+    fn play_sound(&mut self, key: &str, volume: f32) {
+        if let Some(sound) = self.decode(key) {
+            let sink = Sink::new(&self.device);
+            sink.set_volume(volume);
+            sink.append(sound);
+            // A detached sink keeps playing until the sound is over
+            sink.detach();
+        }
+    }
+
+    fn play_music(&mut self, key: &str, volume: f32) {
+        self.stop_music();
+        if let Some(track) = self.decode(key) {
+            let sink = Sink::new(&self.device);
+            sink.set_volume(volume);
+            sink.append(track.repeat_infinite());
+            self.music = Some(sink);
+        }
+    }
+
+    fn stop_music(&mut self) {
+        if let Some(music) = self.music.take() {
+            music.stop();
+        }
+    }
+}
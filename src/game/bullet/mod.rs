@@ -1,28 +1,39 @@
+use crate::config::settings::Projectiles;
+use crate::game::animation::hitbox::Hitboxes;
+use crate::game::collision::layers::Layer;
+use crate::game::health::{Damage, DamageKind};
 use crate::game::insertable::{Insertable, Inserted, InsertedBody};
+use crate::game::navigation::grid::Bounds;
 use nalgebra::{Isometry2, Vector2};
-use ncollide2d::shape::{Cuboid, ShapeHandle};
 use nphysics2d::algebra::Velocity2;
 use nphysics2d::object::{
     ColliderDesc, DefaultBodyHandle, DefaultBodySet, RigidBody, RigidBodyDesc,
 };
-use opengl_graphics::{Texture, TextureSettings};
+use opengl_graphics::Texture;
 use sprite::Scene;
-use std::rc::Rc;
 use uuid::Uuid;
 
 const BULLET_BODY_WIDTH: f64 = 5.0;
 const BULLET_BODY_HEIGHT: f64 = 5.0;
 const BULLET_SPAWN_OFFSET: f64 = 35.0;
-const BULLET_SPEED: f64 = 250.0;
+const BULLET_TEXTURE: &str = "vaccine.png";
 
 #[derive(Clone)]
 pub struct BulletUserData {
     damage: u32,
+    // Where the bullet was fired from
+    origin: Vector2<f64>,
     pub uuid: Uuid,
 }
 
 pub struct InsertedBullet {
     inserted: Inserted,
+    damage: Damage,
+    origin: Vector2<f64>,
+    // Seconds since the bullet was fired
+    age: f64,
+    max_lifetime: f64,
+    max_distance: f64,
 }
 
 impl BulletUserData {
@@ -30,57 +41,58 @@ impl BulletUserData {
     pub fn generate_insertable(
         initial_position: Vector2<f64>,
         rotation_rad: f64,
-    ) -> (Insertable, Uuid) {
-        let bullet_uuid = Uuid::new_v4();
-        let bullet_collider = BulletUserData::generate_bullet_collider_desc();
+        speed: f64,
+        damage: u32,
+        hitboxes: &Hitboxes,
+    ) -> (Insertable, BulletUserData) {
+        let directional_unit_vector = BulletUserData::bullet_directional_unit_vector(rotation_rad);
+        let user_data = BulletUserData {
+            damage,
+            origin: initial_position + directional_unit_vector * BULLET_SPAWN_OFFSET,
+            uuid: Uuid::new_v4(),
+        };
+        let bullet_collider = BulletUserData::generate_bullet_collider_desc(hitboxes);
         let bullet_body =
-            BulletUserData::generate_bullet_body(initial_position, rotation_rad, bullet_uuid);
-        let tex = BulletUserData::generate_bullet_texture();
+            BulletUserData::generate_bullet_body(rotation_rad, speed, user_data.clone());
 
         (
-            Insertable::new(tex, bullet_body, Some(bullet_collider)),
-            bullet_uuid,
+            Insertable::new(
+                BULLET_TEXTURE,
+                bullet_body,
+                Some(bullet_collider),
+                Layer::PlayerProjectile,
+            ),
+            user_data,
         )
     }
 
-    fn generate_bullet_collider_desc() -> ColliderDesc<f64> {
-        let bullet_shape = ShapeHandle::new(Cuboid::new(Vector2::new(
-            BULLET_BODY_WIDTH,
-            BULLET_BODY_HEIGHT,
-        )));
+    fn generate_bullet_collider_desc(hitboxes: &Hitboxes) -> ColliderDesc<f64> {
+        let bullet_shape = hitboxes.shape_or_box(
+            BULLET_TEXTURE,
+            Vector2::new(BULLET_BODY_WIDTH, BULLET_BODY_HEIGHT),
+        );
 
         ColliderDesc::new(bullet_shape).density(0.1)
     }
 
-    fn generate_bullet_texture() -> Rc<Texture> {
-        let assets = find_folder::Search::ParentsThenKids(3, 3)
-            .for_folder("assets")
-            .unwrap();
-
-        Rc::new(Texture::from_path(assets.join("vaccine.png"), &TextureSettings::new()).unwrap())
-    }
-
     fn generate_bullet_body(
-        initial_position: Vector2<f64>,
         rotation_rad: f64,
-        bullet_uuid: Uuid,
+        speed: f64,
+        user_data: BulletUserData,
     ) -> RigidBody<f64> {
         let directional_unit_vector = BulletUserData::bullet_directional_unit_vector(rotation_rad);
         let velocity_vector: Vector2<f64> = Vector2::new(
-            directional_unit_vector[0] * BULLET_SPEED,
-            directional_unit_vector[1] * BULLET_SPEED,
+            directional_unit_vector[0] * speed,
+            directional_unit_vector[1] * speed,
         );
 
         RigidBodyDesc::new()
             .position(Isometry2::translation(
-                initial_position[0] + BULLET_SPAWN_OFFSET * directional_unit_vector[0],
-                initial_position[1] + BULLET_SPAWN_OFFSET * directional_unit_vector[1],
+                user_data.origin[0],
+                user_data.origin[1],
             ))
             .velocity(Velocity2::new(velocity_vector, 0.0))
-            .user_data(BulletUserData {
-                damage: 100,
-                uuid: bullet_uuid,
-            })
+            .user_data(user_data)
             .max_angular_velocity(0.0)
             .rotation(rotation_rad)
             .build()
@@ -98,14 +110,41 @@ impl BulletUserData {
 }
 
 impl InsertedBullet {
-    pub fn new(inserted: Inserted) -> Self {
-        InsertedBullet { inserted }
+    pub fn new(inserted: Inserted, user_data: &BulletUserData, config: &Projectiles) -> Self {
+        InsertedBullet {
+            inserted,
+            damage: Damage::new(user_data.damage, DamageKind::Vaccine),
+            origin: user_data.origin,
+            age: 0.0,
+            max_lifetime: config.max_lifetime,
+            max_distance: config.max_distance,
+        }
+    }
+
+    pub fn damage(&self) -> Damage {
+        self.damage
+    }
+
+    pub fn advance_age(&mut self, dt: f64) {
+        self.age += dt;
+    }
+
+    /// True once the bullet has been around too long, travelled too far or left `bounds`.
+    /// A bullet whose body is already gone is always expired.
+    pub fn is_expired(&self, world: &DefaultBodySet<f64>, bounds: &Bounds) -> bool {
+        let position = match world.rigid_body(self.inserted.get_body_handle()) {
+            Some(rigid_body) => rigid_body.position().translation.vector,
+            None => return true,
+        };
+        self.age >= self.max_lifetime
+            || (position - self.origin).norm() >= self.max_distance
+            || !bounds.contains(position)
     }
 }
 
 impl InsertedBody for InsertedBullet {
     fn update(&self, world: &DefaultBodySet<f64>, scene: &mut Scene<Texture>) {
-        if let Some(bullet_sprite) = scene.child_mut(self.inserted.get_sprite_uuid()) {
+        if let Some(bullet_sprite) = self.inserted.get_sprite_mut(scene) {
             if let Some(rigid_body) = world.rigid_body(self.inserted.get_body_handle()) {
                 let rigid_body_pos = rigid_body.position().translation.vector;
                 let (x_pos, y_pos) = (rigid_body_pos[0], rigid_body_pos[1]);
@@ -118,7 +157,89 @@ impl InsertedBody for InsertedBullet {
         self.inserted.get_body_handle()
     }
 
-    fn get_sprite_uuid(&self) -> Uuid {
+    fn get_sprite_uuid(&self) -> Option<Uuid> {
         self.inserted.get_sprite_uuid()
     }
+
+    fn inserted(&self) -> &Inserted {
+        &self.inserted
+    }
+
+    fn inserted_mut(&mut self) -> &mut Inserted {
+        &mut self.inserted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::insertable::PhysicsInserted;
+
+    const BOUNDS: Bounds = Bounds {
+        x: 0.0,
+        y: 0.0,
+        width: 1000.0,
+        height: 1000.0,
+    };
+
+    // A bullet fired from (100, 100) that lasts 2 seconds or 300 units, with its body placed at `position`
+    fn bullet(body_set: &mut DefaultBodySet<f64>, position: Vector2<f64>) -> InsertedBullet {
+        let body = RigidBodyDesc::new()
+            .position(Isometry2::translation(position.x, position.y))
+            .build();
+        let body_handle = body_set.insert(body);
+        let inserted =
+            Inserted::new_from_physics(BULLET_TEXTURE, PhysicsInserted::new(body_handle, None));
+        let user_data = BulletUserData {
+            damage: 1,
+            origin: Vector2::new(100.0, 100.0),
+            uuid: Uuid::new_v4(),
+        };
+        let config = Projectiles {
+            max_lifetime: 2.0,
+            max_distance: 300.0,
+        };
+        InsertedBullet::new(inserted, &user_data, &config)
+    }
+
+    #[test]
+    fn a_fresh_bullet_is_not_expired() {
+        let mut body_set = DefaultBodySet::new();
+        let bullet = bullet(&mut body_set, Vector2::new(150.0, 100.0));
+        assert!(!bullet.is_expired(&body_set, &BOUNDS));
+    }
+
+    #[test]
+    fn bullets_expire_once_their_lifetime_runs_out() {
+        let mut body_set = DefaultBodySet::new();
+        let mut bullet = bullet(&mut body_set, Vector2::new(150.0, 100.0));
+        bullet.advance_age(1.5);
+        assert!(!bullet.is_expired(&body_set, &BOUNDS));
+        bullet.advance_age(0.5);
+        assert!(bullet.is_expired(&body_set, &BOUNDS));
+    }
+
+    #[test]
+    fn bullets_expire_once_they_travel_past_their_range() {
+        let mut body_set = DefaultBodySet::new();
+        let in_range = bullet(&mut body_set, Vector2::new(399.0, 100.0));
+        let out_of_range = bullet(&mut body_set, Vector2::new(100.0, 400.0));
+        assert!(!in_range.is_expired(&body_set, &BOUNDS));
+        assert!(out_of_range.is_expired(&body_set, &BOUNDS));
+    }
+
+    #[test]
+    fn bullets_expire_once_they_leave_the_bounds() {
+        let mut body_set = DefaultBodySet::new();
+        let bullet = bullet(&mut body_set, Vector2::new(-1.0, 100.0));
+        assert!(bullet.is_expired(&body_set, &BOUNDS));
+    }
+
+    #[test]
+    fn bullets_whose_body_is_gone_are_expired() {
+        let mut body_set = DefaultBodySet::new();
+        let bullet = bullet(&mut body_set, Vector2::new(150.0, 100.0));
+        body_set.remove(bullet.get_body_handle());
+        assert!(bullet.is_expired(&body_set, &BOUNDS));
+    }
 }
@@ -0,0 +1,163 @@
+use crate::config::settings::Camera as CameraSettings;
+use crate::game::navigation::grid::Bounds;
+use graphics::Transformed;
+use nalgebra::Vector2;
+use piston_window::math::Matrix2d;
+
+/// Camera decides which part of the level is on screen.
+/// `position` is the point in world space at the center of the view. It eases towards whatever it is following
+/// and is kept inside the level bounds so nothing outside of the level is ever shown.
+pub struct Camera {
+    position: Vector2<f64>,
+    zoom: f64,
+    smoothing: f64,
+    // Size of the view in pixels, starts at the configured window size and follows it when the window is resized
+    viewport: [f64; 2],
+    bounds: Bounds,
+}
+
+impl Camera {
+    pub fn new(
+        config: &CameraSettings,
+        viewport: [f64; 2],
+        bounds: Bounds,
+        target: Vector2<f64>,
+    ) -> Camera {
+        let mut camera = Camera {
+            position: target,
+            zoom: config.zoom,
+            smoothing: config.smoothing,
+            viewport,
+            bounds,
+        };
+        camera.clamp_to_bounds();
+        camera
+    }
+
+    pub fn position(&self) -> Vector2<f64> {
+        self.position
+    }
+
+    /// Changes the size of the view, for when the window has been resized.
+    pub fn set_viewport(&mut self, viewport: [f64; 2]) {
+        if viewport != self.viewport {
+            self.viewport = viewport;
+            self.clamp_to_bounds();
+        }
+    }
+
+    /// Moves the camera towards `target` over `dt` seconds.
+    /// The gap to the target shrinks by the same fraction every second no matter the frame rate.
+    pub fn follow(&mut self, target: Vector2<f64>, dt: f64) {
+        let catch_up = 1.0 - (-self.smoothing * dt).exp();
+        self.position += (target - self.position) * catch_up;
+        self.clamp_to_bounds();
+    }
+
+    /// Keeps the view inside the level. If the level is smaller than the view along an axis it is centered on that axis instead.
+    fn clamp_to_bounds(&mut self) {
+        let half_width = self.viewport[0] / (2.0 * self.zoom);
+        let half_height = self.viewport[1] / (2.0 * self.zoom);
+        self.position.x = Camera::clamp_axis(
+            self.position.x,
+            half_width,
+            self.bounds.x,
+            self.bounds.width,
+        );
+        self.position.y = Camera::clamp_axis(
+            self.position.y,
+            half_height,
+            self.bounds.y,
+            self.bounds.height,
+        );
+    }
+
+    fn clamp_axis(position: f64, half_view: f64, start: f64, length: f64) -> f64 {
+        if length <= half_view * 2.0 {
+            start + length / 2.0
+        } else {
+            position
+                .max(start + half_view)
+                .min(start + length - half_view)
+        }
+    }
+
+    /// Turns a screen space transform into one that draws world space coordinates where the camera shows them.
+    pub fn transform(&self, screen_transform: Matrix2d) -> Matrix2d {
+        screen_transform
+            .trans(self.viewport[0] / 2.0, self.viewport[1] / 2.0)
+            .zoom(self.zoom)
+            .trans(-self.position.x, -self.position.y)
+    }
+
+    /// The inverse of `transform`, turns a point on the screen (like the cursor) into the point in the world under it.
+    pub fn screen_to_world(&self, screen_position: [f64; 2]) -> [f64; 2] {
+        [
+            (screen_position[0] - self.viewport[0] / 2.0) / self.zoom + self.position.x,
+            (screen_position[1] - self.viewport[1] / 2.0) / self.zoom + self.position.y,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphics::math::{identity, transform_pos};
+
+    const BOUNDS: Bounds = Bounds {
+        x: 0.0,
+        y: 0.0,
+        width: 1000.0,
+        height: 800.0,
+    };
+
+    // A 400 by 300 view zoomed in twice, so it shows 200 by 150 units of the level
+    fn camera(target: Vector2<f64>) -> Camera {
+        let config = CameraSettings {
+            zoom: 2.0,
+            smoothing: 5.0,
+        };
+        Camera::new(&config, [400.0, 300.0], BOUNDS, target)
+    }
+
+    #[test]
+    fn the_view_stays_inside_the_level() {
+        let camera = camera(Vector2::new(-50.0, 2000.0));
+        assert_eq!(camera.position(), Vector2::new(100.0, 725.0));
+        let top_left = camera.screen_to_world([0.0, 0.0]);
+        let bottom_right = camera.screen_to_world([400.0, 300.0]);
+        assert!(top_left[0] >= BOUNDS.x && top_left[1] >= BOUNDS.y);
+        assert!(bottom_right[0] <= BOUNDS.x + BOUNDS.width);
+        assert!(bottom_right[1] <= BOUNDS.y + BOUNDS.height);
+    }
+
+    #[test]
+    fn following_never_leaves_the_level() {
+        let mut camera = camera(Vector2::new(500.0, 400.0));
+        for _ in 0..100 {
+            camera.follow(Vector2::new(5000.0, -5000.0), 0.1);
+        }
+        assert_eq!(camera.position(), Vector2::new(900.0, 75.0));
+    }
+
+    #[test]
+    fn a_level_smaller_than_the_view_is_centered() {
+        let mut camera = camera(Vector2::new(500.0, 400.0));
+        camera.set_viewport([4000.0, 300.0]);
+        assert_eq!(camera.position(), Vector2::new(500.0, 400.0));
+        camera.follow(Vector2::new(0.0, 400.0), 1.0);
+        assert_eq!(camera.position(), Vector2::new(500.0, 400.0));
+    }
+
+    #[test]
+    fn screen_to_world_inverts_transform() {
+        let camera = camera(Vector2::new(300.0, 200.0));
+        let transform = camera.transform(identity());
+        for &point in &[[300.0, 200.0], [210.0, 130.0], [389.5, 274.25]] {
+            let screen = transform_pos(transform, point);
+            let world = camera.screen_to_world(screen);
+            assert!((world[0] - point[0]).abs() < 1e-9);
+            assert!((world[1] - point[1]).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::config::settings::Collision;
+use ncollide2d::pipeline::CollisionGroups;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// The collision layer an entity belongs to. Every collider in the physics world is on exactly one layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Layer {
+    Player,
+    Enemy,
+    PlayerProjectile,
+    StaticWorld,
+    Pickup,
+}
+
+impl Layer {
+    /// The ncollide collision group used for this layer.
+    fn group(self) -> usize {
+        self as usize
+    }
+}
+
+impl TryFrom<String> for Layer {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        match name.as_str() {
+            "player" => Ok(Layer::Player),
+            "enemy" => Ok(Layer::Enemy),
+            "player_projectile" => Ok(Layer::PlayerProjectile),
+            "static_world" => Ok(Layer::StaticWorld),
+            "pickup" => Ok(Layer::Pickup),
+            _ => Err(format!("unknown collision layer {}", name)),
+        }
+    }
+}
+
+/// LayerMatrix decides which layers are allowed to collide with each other.
+/// Interactions are always two way, so a pair only needs to be listed under one of its layers in the config.
+pub struct LayerMatrix {
+    interactions: HashMap<Layer, Vec<Layer>>,
+}
+
+impl LayerMatrix {
+    pub fn new(config: &Collision) -> LayerMatrix {
+        let mut matrix = LayerMatrix {
+            interactions: HashMap::new(),
+        };
+        let configured = [
+            (Layer::Player, &config.player),
+            (Layer::Enemy, &config.enemy),
+            (Layer::PlayerProjectile, &config.player_projectile),
+            (Layer::StaticWorld, &config.static_world),
+            (Layer::Pickup, &config.pickup),
+        ];
+        for (layer, interacts_with) in configured.iter() {
+            for other in interacts_with.iter() {
+                matrix.allow(*layer, *other);
+                matrix.allow(*other, *layer);
+            }
+        }
+        matrix
+    }
+
+    fn allow(&mut self, layer: Layer, other: Layer) {
+        let interactions = self.interactions.entry(layer).or_insert_with(Vec::new);
+        if !interactions.contains(&other) {
+            interactions.push(other);
+        }
+    }
+
+    /// Builds the collision groups for a collider on `layer`.
+    pub fn collision_groups(&self, layer: Layer) -> CollisionGroups {
+        let whitelist: Vec<usize> = match self.interactions.get(&layer) {
+            Some(interactions) => interactions.iter().map(|other| other.group()).collect(),
+            None => vec![],
+        };
+        CollisionGroups::new()
+            .with_membership(&[layer.group()])
+            .with_whitelist(&whitelist)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The interactions from the default config
+    fn matrix() -> LayerMatrix {
+        LayerMatrix::new(&Collision {
+            player: vec![Layer::Enemy, Layer::StaticWorld, Layer::Pickup],
+            enemy: vec![Layer::Enemy, Layer::PlayerProjectile, Layer::StaticWorld],
+            player_projectile: vec![Layer::StaticWorld],
+            static_world: vec![],
+            pickup: vec![],
+        })
+    }
+
+    fn interact(matrix: &LayerMatrix, layer: Layer, other: Layer) -> bool {
+        matrix
+            .collision_groups(layer)
+            .can_interact_with_groups(&matrix.collision_groups(other))
+    }
+
+    #[test]
+    fn player_projectiles_never_hit_the_player_or_each_other() {
+        let matrix = matrix();
+        assert!(!interact(&matrix, Layer::PlayerProjectile, Layer::Player));
+        assert!(!interact(&matrix, Layer::Player, Layer::PlayerProjectile));
+        assert!(!interact(
+            &matrix,
+            Layer::PlayerProjectile,
+            Layer::PlayerProjectile
+        ));
+    }
+
+    #[test]
+    fn interactions_listed_under_one_layer_go_both_ways() {
+        let matrix = matrix();
+        assert!(interact(&matrix, Layer::PlayerProjectile, Layer::Enemy));
+        assert!(interact(&matrix, Layer::Enemy, Layer::PlayerProjectile));
+        assert!(interact(&matrix, Layer::StaticWorld, Layer::Player));
+        assert!(interact(&matrix, Layer::Pickup, Layer::Player));
+    }
+
+    #[test]
+    fn unlisted_layers_do_not_interact() {
+        let matrix = matrix();
+        assert!(!interact(&matrix, Layer::Enemy, Layer::Pickup));
+        assert!(!interact(&matrix, Layer::StaticWorld, Layer::StaticWorld));
+        assert!(!interact(&matrix, Layer::Pickup, Layer::Pickup));
+    }
+
+    #[test]
+    fn layers_are_read_from_their_config_names() {
+        assert_eq!(
+            Layer::try_from("player_projectile".to_string()),
+            Ok(Layer::PlayerProjectile)
+        );
+        assert!(Layer::try_from("enemy_projectile".to_string()).is_err());
+    }
+}
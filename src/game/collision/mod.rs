@@ -0,0 +1,118 @@
+use crate::game::entity::{EntityId, EntityKind};
+use crate::game::world::World;
+use std::collections::HashMap;
+
+pub mod layers;
+
+/// Called when two entities start or stop touching, or when a sensor starts overlapping something.
+/// The ids are passed in the same order as the kinds the handler was registered with.
+pub type CollisionHandler = fn(&mut World, EntityId, EntityId);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ContactPhase {
+    Started,
+    Stopped,
+    /// A sensor collider started overlapping another collider. Sensors never touch, so they only ever have this phase.
+    Sensed,
+}
+
+/// A table of what should happen when two kinds of entities collide.
+/// Each pair of kinds only needs to be registered once, in either order.
+pub struct CollisionHandlers {
+    handlers: HashMap<(ContactPhase, EntityKind, EntityKind), CollisionHandler>,
+}
+
+impl CollisionHandlers {
+    pub fn new() -> CollisionHandlers {
+        CollisionHandlers {
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Registers a handler for when two kinds of entities start touching.
+    pub fn register(&mut self, first: EntityKind, second: EntityKind, handler: CollisionHandler) {
+        self.handlers
+            .insert((ContactPhase::Started, first, second), handler);
+    }
+
+    /// Registers a handler for when two kinds of entities stop touching.
+    pub fn register_stopped(
+        &mut self,
+        first: EntityKind,
+        second: EntityKind,
+        handler: CollisionHandler,
+    ) {
+        self.handlers
+            .insert((ContactPhase::Stopped, first, second), handler);
+    }
+
+    /// Registers a handler for when a sensor on one kind of entity starts overlapping another kind.
+    pub fn register_sensed(
+        &mut self,
+        first: EntityKind,
+        second: EntityKind,
+        handler: CollisionHandler,
+    ) {
+        self.handlers
+            .insert((ContactPhase::Sensed, first, second), handler);
+    }
+
+    /// Finds the handler for two colliding entities along with the ids ordered to match the handler.
+    pub fn get(
+        &self,
+        phase: ContactPhase,
+        first: EntityId,
+        second: EntityId,
+    ) -> Option<(CollisionHandler, EntityId, EntityId)> {
+        if let Some(handler) = self.handlers.get(&(phase, first.kind, second.kind)) {
+            Some((*handler, first, second))
+        } else if let Some(handler) = self.handlers.get(&(phase, second.kind, first.kind)) {
+            Some((*handler, second, first))
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for CollisionHandlers {
+    fn default() -> Self {
+        CollisionHandlers::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn ignore(_world: &mut World, _first: EntityId, _second: EntityId) {}
+
+    fn id(kind: EntityKind) -> EntityId {
+        EntityId::new(kind, Uuid::new_v4())
+    }
+
+    #[test]
+    fn handlers_get_their_ids_in_the_order_they_were_registered() {
+        let mut handlers = CollisionHandlers::new();
+        handlers.register(EntityKind::Bullet, EntityKind::Enemy, ignore);
+        let (bullet, enemy) = (id(EntityKind::Bullet), id(EntityKind::Enemy));
+
+        let (_, first, second) = handlers.get(ContactPhase::Started, bullet, enemy).unwrap();
+        assert_eq!((first, second), (bullet, enemy));
+
+        let (_, first, second) = handlers.get(ContactPhase::Started, enemy, bullet).unwrap();
+        assert_eq!((first, second), (bullet, enemy));
+    }
+
+    #[test]
+    fn handlers_only_answer_for_their_phase_and_kinds() {
+        let mut handlers = CollisionHandlers::new();
+        handlers.register(EntityKind::Player, EntityKind::Enemy, ignore);
+        let (player, enemy) = (id(EntityKind::Player), id(EntityKind::Enemy));
+        assert!(handlers.get(ContactPhase::Stopped, player, enemy).is_none());
+        assert!(handlers.get(ContactPhase::Sensed, enemy, player).is_none());
+        assert!(handlers
+            .get(ContactPhase::Started, player, id(EntityKind::Pickup))
+            .is_none());
+    }
+}
@@ -0,0 +1,188 @@
+use crate::game::navigation::Heading;
+use nalgebra::Vector2;
+use nphysics2d::algebra::{Force2, ForceType};
+use nphysics2d::object::{Body, RigidBody};
+
+/// How an enemy moves. Each enemy type picks its own numbers.
+#[derive(Clone, Copy, Debug)]
+pub struct Steering {
+    pub max_speed: f64,
+    pub max_acceleration: f64,
+    /// Within this distance of its target the enemy starts slowing down so it doesn't overshoot.
+    pub arrive_radius: f64,
+    /// Enemies closer together than this push away from each other.
+    pub separation_radius: f64,
+    /// How strongly separation is weighted against chasing the target.
+    pub separation_weight: f64,
+}
+
+impl Steering {
+    /// The acceleration that takes an enemy at `position` moving at `velocity` toward `target` at full speed.
+    pub fn seek(
+        &self,
+        position: Vector2<f64>,
+        velocity: Vector2<f64>,
+        target: Vector2<f64>,
+    ) -> Vector2<f64> {
+        let to_target = target - position;
+        let distance = to_target.norm();
+        if distance < std::f64::EPSILON {
+            return Vector2::zeros();
+        }
+        to_target / distance * self.max_speed - velocity
+    }
+
+    /// The acceleration that takes an enemy at `position` moving at `velocity` to `target`.
+    /// The enemy moves at max_speed until it is inside the arrive radius, then slows down the closer it gets.
+    pub fn arrive(
+        &self,
+        position: Vector2<f64>,
+        velocity: Vector2<f64>,
+        target: Vector2<f64>,
+    ) -> Vector2<f64> {
+        let to_target = target - position;
+        let distance = to_target.norm();
+        if distance < std::f64::EPSILON {
+            return -velocity;
+        }
+        let speed = if distance < self.arrive_radius {
+            self.max_speed * distance / self.arrive_radius
+        } else {
+            self.max_speed
+        };
+        let desired_velocity = to_target / distance * speed;
+        desired_velocity - velocity
+    }
+
+    /// Pushes away from every neighbour inside the separation radius, harder the closer they are.
+    pub fn separation(&self, position: Vector2<f64>, neighbours: &[Vector2<f64>]) -> Vector2<f64> {
+        let mut push = Vector2::zeros();
+        for neighbour in neighbours {
+            let away = position - neighbour;
+            let distance = away.norm();
+            // A distance of zero is the enemy itself
+            if distance > 0.0 && distance < self.separation_radius {
+                push += away / distance * (1.0 - distance / self.separation_radius);
+            }
+        }
+        push * self.max_acceleration
+    }
+
+    /// Combines heading toward the next point on the path with separating from neighbours, limited to the max acceleration.
+    pub fn steer(
+        &self,
+        position: Vector2<f64>,
+        velocity: Vector2<f64>,
+        heading: &Heading,
+        neighbours: &[Vector2<f64>],
+    ) -> Vector2<f64> {
+        let toward = match heading {
+            Heading::Waypoint(waypoint) => self.seek(position, velocity, *waypoint),
+            Heading::Goal(goal) => self.arrive(position, velocity, *goal),
+        };
+        let acceleration = toward + self.separation(position, neighbours) * self.separation_weight;
+        limit(acceleration, self.max_acceleration)
+    }
+
+    /// Steers a rigid body by applying the steering acceleration to it.
+    pub fn apply(&self, body: &mut RigidBody<f64>, heading: &Heading, neighbours: &[Vector2<f64>]) {
+        let position = body.position().translation.vector;
+        let velocity = body.velocity().linear;
+        let acceleration = self.steer(position, velocity, heading, neighbours);
+        body.apply_force(
+            0,
+            &Force2::linear(acceleration),
+            ForceType::AccelerationChange,
+            true,
+        );
+    }
+}
+
+fn limit(vector: Vector2<f64>, max_length: f64) -> Vector2<f64> {
+    let length = vector.norm();
+    if length > max_length {
+        vector / length * max_length
+    } else {
+        vector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEERING: Steering = Steering {
+        max_speed: 100.0,
+        max_acceleration: 50.0,
+        arrive_radius: 40.0,
+        separation_radius: 20.0,
+        separation_weight: 1.0,
+    };
+
+    fn assert_near(actual: Vector2<f64>, expected: Vector2<f64>) {
+        assert!(
+            (actual - expected).norm() < 1e-9,
+            "expected {:?} to be {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn seek_heads_for_the_target_at_full_speed() {
+        let target = Vector2::new(300.0, 0.0);
+        let from_rest = STEERING.seek(Vector2::zeros(), Vector2::zeros(), target);
+        assert_near(from_rest, Vector2::new(100.0, 0.0));
+        // Already moving at full speed toward the target, so there is nothing to change
+        let at_speed = STEERING.seek(Vector2::zeros(), Vector2::new(100.0, 0.0), target);
+        assert_near(at_speed, Vector2::zeros());
+    }
+
+    #[test]
+    fn arrive_moves_at_full_speed_outside_the_radius() {
+        let acceleration =
+            STEERING.arrive(Vector2::zeros(), Vector2::zeros(), Vector2::new(0.0, 80.0));
+        assert_near(acceleration, Vector2::new(0.0, 100.0));
+    }
+
+    #[test]
+    fn arrive_slows_to_zero_at_the_target() {
+        let target = Vector2::new(40.0, 0.0);
+        let velocity = Vector2::new(100.0, 0.0);
+        // Halfway into the arrive radius the enemy should be going at half speed
+        let halfway = STEERING.arrive(Vector2::new(20.0, 0.0), velocity, target);
+        assert_near(halfway + velocity, Vector2::new(50.0, 0.0));
+        // On the target all that is left is cancelling out the velocity
+        let on_target = STEERING.arrive(target, velocity, target);
+        assert_near(on_target + velocity, Vector2::zeros());
+    }
+
+    #[test]
+    fn separation_pushes_away_from_close_neighbours() {
+        let position = Vector2::new(10.0, 10.0);
+        let push = STEERING.separation(position, &[Vector2::new(15.0, 10.0)]);
+        assert_near(push, Vector2::new(-37.5, 0.0));
+        // Closer neighbours push harder
+        let closer = STEERING.separation(position, &[Vector2::new(12.0, 10.0)]);
+        assert!(closer.norm() > push.norm());
+    }
+
+    #[test]
+    fn separation_ignores_far_neighbours_and_the_enemy_itself() {
+        let position = Vector2::new(10.0, 10.0);
+        let push = STEERING.separation(position, &[position, Vector2::new(10.0, 30.0)]);
+        assert_near(push, Vector2::zeros());
+    }
+
+    #[test]
+    fn steering_never_exceeds_the_max_acceleration() {
+        let heading = Heading::Waypoint(Vector2::new(-500.0, 0.0));
+        let acceleration = STEERING.steer(
+            Vector2::zeros(),
+            Vector2::new(100.0, 0.0),
+            &heading,
+            &[Vector2::new(1.0, 0.0)],
+        );
+        assert!(acceleration.norm() <= STEERING.max_acceleration + 1e-9);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::game::animation::hitbox::Hitboxes;
+use crate::game::collision::layers::Layer;
+use crate::game::enemy::ai::Steering;
+use crate::game::health::Health;
+use crate::game::insertable::{Insertable, Inserted, InsertedBody};
+use crate::game::navigation::{Navigation, PathFollower};
+use nalgebra::{Isometry2, Vector2};
+use ncollide2d::shape::ShapeHandle;
+use nphysics2d::object::{ColliderDesc, DefaultBodyHandle, DefaultBodySet, RigidBodyDesc};
+use opengl_graphics::Texture;
+use sprite::Scene;
+use uuid::Uuid;
+
+/// EnemyBody is the part every enemy has in common: what it is in the world, its health and how it moves around.
+pub struct EnemyBody {
+    inserted: Inserted,
+    health: Health,
+    steering: Steering,
+    path: PathFollower,
+}
+
+impl EnemyBody {
+    pub fn new(
+        inserted: Inserted,
+        health: Health,
+        steering: Steering,
+        path: PathFollower,
+    ) -> EnemyBody {
+        EnemyBody {
+            inserted,
+            health,
+            steering,
+            path,
+        }
+    }
+
+    /// The insertable for an enemy whose collider is the hitbox drawn on its texture.
+    /// Without one it gets a box, `half_extents` are half the width and height of the box.
+    pub fn generate_insertable(
+        texture: &'static str,
+        position: Vector2<f64>,
+        half_extents: Vector2<f64>,
+        steering: &Steering,
+        hitboxes: &Hitboxes,
+    ) -> Insertable {
+        let enemy_shape = hitboxes.shape_or_box(texture, half_extents);
+        EnemyBody::generate_insertable_with_shape(
+            texture,
+            position,
+            enemy_shape,
+            steering.max_speed,
+        )
+    }
+
+    /// The insertable for an enemy with any collider shape, such as a compound of several parts.
+    /// The enemy can never move faster than `max_speed`.
+    pub fn generate_insertable_with_shape(
+        texture: &'static str,
+        position: Vector2<f64>,
+        shape: ShapeHandle<f64>,
+        max_speed: f64,
+    ) -> Insertable {
+        let enemy_collider = ColliderDesc::new(shape).density(0.1);
+
+        let enemy_body = RigidBodyDesc::new()
+            .position(Isometry2::translation(position[0], position[1]))
+            .max_linear_velocity(max_speed)
+            .max_angular_velocity(0.0)
+            .build();
+
+        Insertable::new(texture, enemy_body, Some(enemy_collider), Layer::Enemy)
+    }
+
+    pub fn health(&self) -> &Health {
+        &self.health
+    }
+
+    pub fn health_mut(&mut self) -> &mut Health {
+        &mut self.health
+    }
+
+    /// Swaps how the enemy moves, for enemies that change their behaviour partway through a fight.
+    pub fn set_steering(&mut self, steering: Steering) {
+        self.steering = steering;
+    }
+
+    pub fn position(&self, world: &DefaultBodySet<f64>) -> Option<Vector2<f64>> {
+        world
+            .rigid_body(self.inserted.get_body_handle())
+            .map(|rigid_body| rigid_body.position().translation.vector)
+    }
+
+    /// Moves the enemy at exactly `velocity`, ignoring its steering.
+    pub fn set_velocity(&self, world: &mut DefaultBodySet<f64>, velocity: Vector2<f64>) {
+        if let Some(rigid_body) = world.rigid_body_mut(self.inserted.get_body_handle()) {
+            rigid_body.set_linear_velocity(velocity);
+        }
+    }
+
+    /// Steers the enemy along a path to `goal` while keeping it apart from its neighbours.
+    pub fn head_to(
+        &mut self,
+        world: &mut DefaultBodySet<f64>,
+        navigation: &Navigation,
+        goal: Vector2<f64>,
+        neighbours: &[Vector2<f64>],
+    ) {
+        if let Some(rigid_body) = world.rigid_body_mut(self.inserted.get_body_handle()) {
+            let position = rigid_body.position().translation.vector;
+            let heading = self.path.heading(navigation.grid(), position, goal);
+            self.steering.apply(rigid_body, &heading, neighbours);
+        }
+    }
+}
+
+impl InsertedBody for EnemyBody {
+    fn update(&self, world: &DefaultBodySet<f64>, scene: &mut Scene<Texture>) {
+        if let Some(enemy_sprite) = self.inserted.get_sprite_mut(scene) {
+            if let Some(rigid_body) = world.rigid_body(self.inserted.get_body_handle()) {
+                let rigid_body_pos = rigid_body.position().translation.vector;
+                let (x_pos, y_pos) = (rigid_body_pos[0], rigid_body_pos[1]);
+                enemy_sprite.set_position(x_pos, y_pos);
+            }
+        }
+    }
+
+    fn get_body_handle(&self) -> DefaultBodyHandle {
+        self.inserted.get_body_handle()
+    }
+
+    fn get_sprite_uuid(&self) -> Option<Uuid> {
+        self.inserted.get_sprite_uuid()
+    }
+
+    fn inserted(&self) -> &Inserted {
+        &self.inserted
+    }
+
+    fn inserted_mut(&mut self) -> &mut Inserted {
+        &mut self.inserted
+    }
+}
@@ -0,0 +1,280 @@
+use crate::game::animation::hitbox::Hitboxes;
+use crate::game::enemy::ai::Steering;
+use crate::game::enemy::body::EnemyBody;
+use crate::game::enemy::{Enemy, EnemyType, Surroundings};
+use crate::game::health::{Damage, DamageKind, Health, Resistances};
+use crate::game::insertable::{Insertable, Inserted};
+use crate::game::navigation::PathFollower;
+use nalgebra::{Isometry2, Vector2};
+use ncollide2d::shape::{Ball, Compound, Cuboid, ShapeHandle};
+use nphysics2d::object::DefaultBodySet;
+
+const BOSS_TEXTURE: &str = "boss.png";
+const BOSS_HEALTH: u32 = 1500;
+const BOSS_VACCINE_RESISTANCE: f64 = 0.1;
+const BOSS_TORSO_WIDTH: f64 = 70.0;
+const BOSS_TORSO_HEIGHT: f64 = 50.0;
+const BOSS_HEAD_RADIUS: f64 = 35.0;
+const BOSS_HEAD_OFFSET: f64 = 85.0;
+const BOSS_ARM_WIDTH: f64 = 30.0;
+const BOSS_ARM_HEIGHT: f64 = 45.0;
+const BOSS_ARM_OFFSET: f64 = 100.0;
+// Fast enough for the quickest charge, steering keeps it well below this the rest of the time
+const BOSS_MAX_SPEED: f64 = 520.0;
+// Seconds the boss stalks before attacking again after changing phase
+const BOSS_PHASE_CHANGE_PAUSE: f64 = 1.5;
+
+const STALKING: Steering = Steering {
+    max_speed: 40.0,
+    max_acceleration: 100.0,
+    arrive_radius: 150.0,
+    separation_radius: 200.0,
+    separation_weight: 0.5,
+};
+const ENRAGED: Steering = Steering {
+    max_speed: 75.0,
+    max_acceleration: 200.0,
+    arrive_radius: 120.0,
+    separation_radius: 200.0,
+    separation_weight: 0.5,
+};
+
+/// A dash straight at where the player was when the boss finished winding up.
+#[derive(Clone, Copy, Debug)]
+struct Charge {
+    /// Seconds spent stalking between charges.
+    cooldown: f64,
+    /// Seconds the boss stands still before charging, so the player can see it coming.
+    windup: f64,
+    /// Seconds the charge lasts.
+    duration: f64,
+    speed: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Attack {
+    /// Slowly follows the player around.
+    Stalk,
+    Charge(Charge),
+}
+
+/// One part of the fight. The boss moves on to a phase once its health drops to that phase's threshold.
+struct Phase {
+    /// Fraction of max health at or below which this phase starts.
+    health_threshold: f64,
+    steering: Steering,
+    attack: Attack,
+    /// Babies summoned as the phase starts.
+    summons: u32,
+}
+
+const PHASES: [Phase; 3] = [
+    Phase {
+        health_threshold: 1.0,
+        steering: STALKING,
+        attack: Attack::Stalk,
+        summons: 0,
+    },
+    Phase {
+        health_threshold: 0.66,
+        steering: STALKING,
+        attack: Attack::Charge(Charge {
+            cooldown: 3.0,
+            windup: 0.8,
+            duration: 0.6,
+            speed: 400.0,
+        }),
+        summons: 4,
+    },
+    Phase {
+        health_threshold: 0.33,
+        steering: ENRAGED,
+        attack: Attack::Charge(Charge {
+            cooldown: 1.5,
+            windup: 0.5,
+            duration: 0.7,
+            speed: BOSS_MAX_SPEED,
+        }),
+        summons: 6,
+    },
+];
+
+/// Where the boss is in a charge attack.
+#[derive(Clone, Copy, Debug)]
+enum ChargeState {
+    /// Following the player, with this many seconds until it winds up.
+    Stalking(f64),
+    /// Standing still, with this many seconds until it charges.
+    WindingUp(f64),
+    /// Charging in this direction with this many seconds left.
+    Charging(f64, Vector2<f64>),
+}
+
+/// The head of the anti-vax movement. A huge enemy made of several parts that gets more
+/// dangerous as it is worn down, calling in babies each time it changes phase.
+pub struct Boss {
+    body: EnemyBody,
+    // Index into PHASES
+    phase: usize,
+    charge: ChargeState,
+    // Babies called in by a phase change that have not been spawned yet
+    summoned: Vec<EnemyType>,
+}
+
+impl Boss {
+    /// The body is a torso with a head on top and an arm on each side, all stuck to one rigid body.
+    /// Only used until the boss's art has its own hitbox.
+    fn shape() -> ShapeHandle<f64> {
+        let parts = vec![
+            (
+                Isometry2::identity(),
+                ShapeHandle::new(Cuboid::new(Vector2::new(
+                    BOSS_TORSO_WIDTH,
+                    BOSS_TORSO_HEIGHT,
+                ))),
+            ),
+            (
+                Isometry2::translation(0.0, -BOSS_HEAD_OFFSET),
+                ShapeHandle::new(Ball::new(BOSS_HEAD_RADIUS)),
+            ),
+            (
+                Isometry2::translation(-BOSS_ARM_OFFSET, 0.0),
+                ShapeHandle::new(Cuboid::new(Vector2::new(BOSS_ARM_WIDTH, BOSS_ARM_HEIGHT))),
+            ),
+            (
+                Isometry2::translation(BOSS_ARM_OFFSET, 0.0),
+                ShapeHandle::new(Cuboid::new(Vector2::new(BOSS_ARM_WIDTH, BOSS_ARM_HEIGHT))),
+            ),
+        ];
+        ShapeHandle::new(Compound::new(parts))
+    }
+
+    /// Moves on to every phase whose threshold the boss's health has dropped to.
+    fn advance_phase(&mut self) {
+        let health = self.body.health();
+        let fraction = f64::from(health.current()) / f64::from(health.max());
+        while self.phase + 1 < PHASES.len() && fraction <= PHASES[self.phase + 1].health_threshold {
+            self.phase += 1;
+            let phase = &PHASES[self.phase];
+            self.body.set_steering(phase.steering);
+            self.summoned
+                .extend(std::iter::repeat(EnemyType::Baby).take(phase.summons as usize));
+            self.charge = ChargeState::Stalking(BOSS_PHASE_CHANGE_PAUSE);
+        }
+    }
+
+    fn stalk(&mut self, world: &mut DefaultBodySet<f64>, surroundings: &Surroundings) {
+        self.body.head_to(
+            world,
+            surroundings.navigation,
+            surroundings.player_position,
+            surroundings.neighbours,
+        );
+    }
+
+    fn charge(
+        &mut self,
+        charge: Charge,
+        world: &mut DefaultBodySet<f64>,
+        surroundings: &Surroundings,
+    ) {
+        let position = match self.body.position(world) {
+            Some(position) => position,
+            None => return,
+        };
+        self.charge = match self.charge {
+            ChargeState::Stalking(left) => {
+                self.stalk(world, surroundings);
+                let left = left - surroundings.dt;
+                if left <= 0.0 {
+                    ChargeState::WindingUp(charge.windup)
+                } else {
+                    ChargeState::Stalking(left)
+                }
+            }
+            ChargeState::WindingUp(left) => {
+                self.body.set_velocity(world, Vector2::zeros());
+                let left = left - surroundings.dt;
+                if left > 0.0 {
+                    ChargeState::WindingUp(left)
+                } else {
+                    let to_player = surroundings.player_position - position;
+                    let direction = if to_player.norm() > std::f64::EPSILON {
+                        to_player.normalize()
+                    } else {
+                        Vector2::zeros()
+                    };
+                    ChargeState::Charging(charge.duration, direction)
+                }
+            }
+            ChargeState::Charging(left, direction) => {
+                self.body.set_velocity(world, direction * charge.speed);
+                let left = left - surroundings.dt;
+                if left <= 0.0 {
+                    ChargeState::Stalking(charge.cooldown)
+                } else {
+                    ChargeState::Charging(left, direction)
+                }
+            }
+        };
+    }
+}
+
+impl Enemy for Boss {
+    fn spawn(position: Vector2<f64>, hitboxes: &Hitboxes) -> Insertable {
+        EnemyBody::generate_insertable_with_shape(
+            BOSS_TEXTURE,
+            position,
+            hitboxes.shape(BOSS_TEXTURE).unwrap_or_else(Boss::shape),
+            BOSS_MAX_SPEED,
+        )
+    }
+
+    fn new(inserted: Inserted, path: PathFollower) -> Self {
+        Boss {
+            body: EnemyBody::new(
+                inserted,
+                Health::new(
+                    BOSS_HEALTH,
+                    Resistances::default().with(DamageKind::Vaccine, BOSS_VACCINE_RESISTANCE),
+                ),
+                PHASES[0].steering,
+                path,
+            ),
+            phase: 0,
+            charge: ChargeState::Stalking(0.0),
+            summoned: vec![],
+        }
+    }
+
+    fn enemy_type(&self) -> EnemyType {
+        EnemyType::Boss
+    }
+
+    fn body(&self) -> &EnemyBody {
+        &self.body
+    }
+
+    fn body_mut(&mut self) -> &mut EnemyBody {
+        &mut self.body
+    }
+
+    fn behave(&mut self, world: &mut DefaultBodySet<f64>, surroundings: &Surroundings) {
+        match PHASES[self.phase].attack {
+            Attack::Stalk => self.stalk(world, surroundings),
+            Attack::Charge(charge) => self.charge(charge, world, surroundings),
+        }
+    }
+
+    fn take_damage(&mut self, damage: Damage) -> bool {
+        let killed = self.body.health_mut().take_damage(damage);
+        if !killed {
+            self.advance_phase();
+        }
+        killed
+    }
+
+    fn summons(&mut self) -> Vec<EnemyType> {
+        std::mem::take(&mut self.summoned)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::game::animation::hitbox::Hitboxes;
+use crate::game::enemy::ai::Steering;
+use crate::game::enemy::body::EnemyBody;
+use crate::game::enemy::{Enemy, EnemyType};
+use crate::game::health::{Health, Resistances};
+use crate::game::insertable::{Insertable, Inserted};
+use crate::game::navigation::PathFollower;
+use nalgebra::Vector2;
+
+const CRAWLER_BODY_WIDTH: f64 = 30.0;
+const CRAWLER_BODY_HEIGHT: f64 = 15.0;
+const CRAWLER_TEXTURE: &str = "crawler.png";
+const CRAWLER_HEALTH: u32 = 15;
+const CRAWLER_STEERING: Steering = Steering {
+    max_speed: 160.0,
+    max_acceleration: 450.0,
+    arrive_radius: 30.0,
+    separation_radius: 70.0,
+    separation_weight: 1.0,
+};
+
+/// A small, fast baby that goes down in a single hit.
+pub struct Crawler {
+    body: EnemyBody,
+}
+
+impl Enemy for Crawler {
+    fn spawn(position: Vector2<f64>, hitboxes: &Hitboxes) -> Insertable {
+        EnemyBody::generate_insertable(
+            CRAWLER_TEXTURE,
+            position,
+            Vector2::new(CRAWLER_BODY_WIDTH, CRAWLER_BODY_HEIGHT),
+            &CRAWLER_STEERING,
+            hitboxes,
+        )
+    }
+
+    fn new(inserted: Inserted, path: PathFollower) -> Self {
+        Crawler {
+            body: EnemyBody::new(
+                inserted,
+                Health::new(CRAWLER_HEALTH, Resistances::default()),
+                CRAWLER_STEERING,
+                path,
+            ),
+        }
+    }
+
+    fn enemy_type(&self) -> EnemyType {
+        EnemyType::Crawler
+    }
+
+    fn body(&self) -> &EnemyBody {
+        &self.body
+    }
+
+    fn body_mut(&mut self) -> &mut EnemyBody {
+        &mut self.body
+    }
+}
@@ -1,5 +1,115 @@
+use crate::game::animation::hitbox::Hitboxes;
+use crate::game::enemy::body::EnemyBody;
+use crate::game::health::Damage;
+use crate::game::insertable::{Insertable, Inserted};
+use crate::game::navigation::{Navigation, PathFollower};
+use nalgebra::Vector2;
+use nphysics2d::object::DefaultBodySet;
+use std::convert::TryFrom;
+
+pub mod ai;
 pub mod baby;
+pub mod body;
+pub mod boss;
+pub mod crawler;
+pub mod parent;
+pub mod toddler;
+
+/// What an enemy knows about the world when it decides what to do.
+pub struct Surroundings<'a> {
+    pub navigation: &'a Navigation,
+    pub player_position: Vector2<f64>,
+    /// Every enemy in the world, including the one deciding.
+    pub enemies: &'a [(EnemyType, Vector2<f64>)],
+    /// The positions out of `enemies`, for steering apart from each other.
+    pub neighbours: &'a [Vector2<f64>],
+    /// Seconds since the last tick.
+    pub dt: f64,
+}
+
+/// Protection one enemy gives to others near it.
+#[derive(Clone, Copy, Debug)]
+pub struct Shield {
+    pub radius: f64,
+    /// How much of the damage a shielded enemy ignores, from 0.0 (none of it) to 1.0 (all of it).
+    pub damage_reduction: f64,
+}
+
+/// Enemy is implemented by every archetype. The World only ever deals with enemies through it,
+/// so waves and collisions work the same no matter which archetypes are in play.
+pub trait Enemy {
+    /// The insertable for a new enemy of this archetype at `position`.
+    fn spawn(position: Vector2<f64>, hitboxes: &Hitboxes) -> Insertable
+    where
+        Self: Sized;
+
+    /// Builds the enemy once its insertable has been put into the world.
+    fn new(inserted: Inserted, path: PathFollower) -> Self
+    where
+        Self: Sized;
+
+    fn enemy_type(&self) -> EnemyType;
+
+    fn body(&self) -> &EnemyBody;
+
+    fn body_mut(&mut self) -> &mut EnemyBody;
+
+    /// Decides what the enemy does this tick. Unless an archetype says otherwise it chases the player.
+    fn behave(&mut self, world: &mut DefaultBodySet<f64>, surroundings: &Surroundings) {
+        self.body_mut().head_to(
+            world,
+            surroundings.navigation,
+            surroundings.player_position,
+            surroundings.neighbours,
+        );
+    }
+
+    /// Deals damage to the enemy. Returns true only for the hit that kills it.
+    fn take_damage(&mut self, damage: Damage) -> bool {
+        self.body_mut().health_mut().take_damage(damage)
+    }
+
+    /// Enemies that appear where this one died.
+    fn on_death(&self) -> Vec<EnemyType> {
+        vec![]
+    }
+
+    /// Enemies this one calls in this tick, spawned around it.
+    fn summons(&mut self) -> Vec<EnemyType> {
+        vec![]
+    }
+
+    /// The shield this enemy puts up around itself, if it protects other enemies.
+    fn shield(&self) -> Option<Shield> {
+        None
+    }
+
+    /// Tells the enemy which shield, if any, it is inside of this tick. Only enemies that can be protected care.
+    fn set_shield(&mut self, _shield: Option<Shield>) {}
+}
+
+/// The kinds of enemies that can be spawned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum EnemyType {
+    Baby,
+    Crawler,
+    Toddler,
+    Parent,
+    Boss,
+}
+
+impl TryFrom<String> for EnemyType {
+    type Error = String;
 
-trait Enemy {
-    fn spawn() {}
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        match name.as_str() {
+            "baby" => Ok(EnemyType::Baby),
+            "crawler" => Ok(EnemyType::Crawler),
+            "toddler" => Ok(EnemyType::Toddler),
+            "parent" => Ok(EnemyType::Parent),
+            "boss" => Ok(EnemyType::Boss),
+            _ => Err(format!("unknown enemy type {}", name)),
+        }
+    }
 }
@@ -0,0 +1,103 @@
+use crate::game::animation::hitbox::Hitboxes;
+use crate::game::enemy::ai::Steering;
+use crate::game::enemy::body::EnemyBody;
+use crate::game::enemy::{Enemy, EnemyType, Shield, Surroundings};
+use crate::game::health::{Health, Resistances};
+use crate::game::insertable::{Insertable, Inserted};
+use crate::game::navigation::PathFollower;
+use nalgebra::Vector2;
+use nphysics2d::object::DefaultBodySet;
+
+const PARENT_BODY_WIDTH: f64 = 45.0;
+const PARENT_BODY_HEIGHT: f64 = 45.0;
+const PARENT_TEXTURE: &str = "parent.png";
+const PARENT_HEALTH: u32 = 80;
+// Babies further away than this are left to fend for themselves
+const PARENT_GUARD_RADIUS: f64 = 500.0;
+const PARENT_SHIELD: Shield = Shield {
+    radius: 180.0,
+    damage_reduction: 0.75,
+};
+const PARENT_STEERING: Steering = Steering {
+    max_speed: 70.0,
+    max_acceleration: 180.0,
+    arrive_radius: 100.0,
+    separation_radius: 120.0,
+    separation_weight: 1.5,
+};
+
+/// An anti-vax parent. It hangs back with the babies near it instead of chasing the player,
+/// and babies inside its shield shrug off most of the vaccine they are hit with.
+pub struct Parent {
+    body: EnemyBody,
+}
+
+impl Enemy for Parent {
+    fn spawn(position: Vector2<f64>, hitboxes: &Hitboxes) -> Insertable {
+        EnemyBody::generate_insertable(
+            PARENT_TEXTURE,
+            position,
+            Vector2::new(PARENT_BODY_WIDTH, PARENT_BODY_HEIGHT),
+            &PARENT_STEERING,
+            hitboxes,
+        )
+    }
+
+    fn new(inserted: Inserted, path: PathFollower) -> Self {
+        Parent {
+            body: EnemyBody::new(
+                inserted,
+                Health::new(PARENT_HEALTH, Resistances::default()),
+                PARENT_STEERING,
+                path,
+            ),
+        }
+    }
+
+    fn enemy_type(&self) -> EnemyType {
+        EnemyType::Parent
+    }
+
+    fn body(&self) -> &EnemyBody {
+        &self.body
+    }
+
+    fn body_mut(&mut self) -> &mut EnemyBody {
+        &mut self.body
+    }
+
+    /// Heads for the middle of the babies it is guarding, or for the player once there are none left.
+    fn behave(&mut self, world: &mut DefaultBodySet<f64>, surroundings: &Surroundings) {
+        let position = match self.body.position(world) {
+            Some(position) => position,
+            None => return,
+        };
+        let guarded: Vec<Vector2<f64>> = surroundings
+            .enemies
+            .iter()
+            .filter(|(enemy_type, baby_position)| {
+                *enemy_type == EnemyType::Baby
+                    && (baby_position - position).norm() < PARENT_GUARD_RADIUS
+            })
+            .map(|(_, baby_position)| *baby_position)
+            .collect();
+        let goal = if guarded.is_empty() {
+            surroundings.player_position
+        } else {
+            guarded
+                .iter()
+                .fold(Vector2::zeros(), |sum, baby| sum + baby)
+                / guarded.len() as f64
+        };
+        self.body.head_to(
+            world,
+            surroundings.navigation,
+            goal,
+            surroundings.neighbours,
+        );
+    }
+
+    fn shield(&self) -> Option<Shield> {
+        Some(PARENT_SHIELD)
+    }
+}
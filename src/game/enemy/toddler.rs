@@ -0,0 +1,68 @@
+use crate::game::animation::hitbox::Hitboxes;
+use crate::game::enemy::ai::Steering;
+use crate::game::enemy::body::EnemyBody;
+use crate::game::enemy::{Enemy, EnemyType};
+use crate::game::health::{DamageKind, Health, Resistances};
+use crate::game::insertable::{Insertable, Inserted};
+use crate::game::navigation::PathFollower;
+use nalgebra::Vector2;
+
+const TODDLER_BODY_WIDTH: f64 = 40.0;
+const TODDLER_BODY_HEIGHT: f64 = 40.0;
+const TODDLER_TEXTURE: &str = "toddler.png";
+const TODDLER_HEALTH: u32 = 150;
+const TODDLER_VACCINE_RESISTANCE: f64 = 0.2;
+const TODDLER_STEERING: Steering = Steering {
+    max_speed: 50.0,
+    max_acceleration: 120.0,
+    arrive_radius: 80.0,
+    separation_radius: 130.0,
+    separation_weight: 2.0,
+};
+
+/// A slow, tough enemy that leaves two crawlers behind when it goes down.
+pub struct Toddler {
+    body: EnemyBody,
+}
+
+impl Enemy for Toddler {
+    fn spawn(position: Vector2<f64>, hitboxes: &Hitboxes) -> Insertable {
+        EnemyBody::generate_insertable(
+            TODDLER_TEXTURE,
+            position,
+            Vector2::new(TODDLER_BODY_WIDTH, TODDLER_BODY_HEIGHT),
+            &TODDLER_STEERING,
+            hitboxes,
+        )
+    }
+
+    fn new(inserted: Inserted, path: PathFollower) -> Self {
+        Toddler {
+            body: EnemyBody::new(
+                inserted,
+                Health::new(
+                    TODDLER_HEALTH,
+                    Resistances::default().with(DamageKind::Vaccine, TODDLER_VACCINE_RESISTANCE),
+                ),
+                TODDLER_STEERING,
+                path,
+            ),
+        }
+    }
+
+    fn enemy_type(&self) -> EnemyType {
+        EnemyType::Toddler
+    }
+
+    fn body(&self) -> &EnemyBody {
+        &self.body
+    }
+
+    fn body_mut(&mut self) -> &mut EnemyBody {
+        &mut self.body
+    }
+
+    fn on_death(&self) -> Vec<EnemyType> {
+        vec![EnemyType::Crawler, EnemyType::Crawler]
+    }
+}
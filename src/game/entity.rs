@@ -0,0 +1,70 @@
+use crate::game::insertable::Inserted;
+use nphysics2d::object::DefaultColliderHandle;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// The different kinds of things that can exist in the world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EntityKind {
+    Player,
+    /// Any enemy archetype, the World looks up which one through the Enemy trait.
+    Enemy,
+    Bullet,
+    Pickup,
+    /// A piece of the level's static geometry.
+    Wall,
+}
+
+/// Identifies a single entity. The uuid is the key the entity is stored under in the World.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EntityId {
+    pub kind: EntityKind,
+    pub uuid: Uuid,
+}
+
+impl EntityId {
+    pub fn new(kind: EntityKind, uuid: Uuid) -> EntityId {
+        EntityId { kind, uuid }
+    }
+}
+
+/// EntityRegistry maps the handles nphysics gives back to us to the entities that own them.
+/// This lets contact events be resolved with a lookup instead of inspecting the user data on every body.
+pub struct EntityRegistry {
+    colliders: HashMap<DefaultColliderHandle, EntityId>,
+}
+
+impl EntityRegistry {
+    pub fn new() -> EntityRegistry {
+        EntityRegistry {
+            colliders: HashMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, id: EntityId, inserted: &Inserted) {
+        if let Some(collider_handle) = inserted.get_collider_handle() {
+            self.register_collider(id, collider_handle);
+        }
+    }
+
+    /// Registers a collider that isn't part of an Inserted, such as the level's walls.
+    pub fn register_collider(&mut self, id: EntityId, collider_handle: DefaultColliderHandle) {
+        self.colliders.insert(collider_handle, id);
+    }
+
+    pub fn unregister(&mut self, inserted: &Inserted) {
+        if let Some(collider_handle) = inserted.get_collider_handle() {
+            self.colliders.remove(&collider_handle);
+        }
+    }
+
+    pub fn by_collider(&self, collider_handle: DefaultColliderHandle) -> Option<EntityId> {
+        self.colliders.get(&collider_handle).copied()
+    }
+}
+
+impl Default for EntityRegistry {
+    fn default() -> Self {
+        EntityRegistry::new()
+    }
+}
@@ -0,0 +1,146 @@
+use crate::game::entity::EntityId;
+use nalgebra::Vector2;
+use std::collections::HashMap;
+
+/// What dealt some damage. Resistances are given per kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DamageKind {
+    Vaccine,
+    /// Damage from an enemy touching the player.
+    Contact,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Damage {
+    pub amount: u32,
+    pub kind: DamageKind,
+}
+
+impl Damage {
+    pub fn new(amount: u32, kind: DamageKind) -> Damage {
+        Damage { amount, kind }
+    }
+}
+
+/// How much of each kind of damage is ignored, from 0.0 (none of it) to 1.0 (all of it).
+#[derive(Clone, Debug, Default)]
+pub struct Resistances {
+    resistances: HashMap<DamageKind, f64>,
+}
+
+impl Resistances {
+    pub fn with(mut self, kind: DamageKind, resistance: f64) -> Resistances {
+        self.resistances.insert(kind, resistance.max(0.0).min(1.0));
+        self
+    }
+
+    /// The damage left over after the resistance for its kind is applied.
+    pub fn reduce(&self, damage: Damage) -> u32 {
+        match self.resistances.get(&damage.kind) {
+            Some(resistance) => (f64::from(damage.amount) * (1.0 - resistance)).round() as u32,
+            None => damage.amount,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Health {
+    current: u32,
+    max: u32,
+    resistances: Resistances,
+}
+
+impl Health {
+    pub fn new(max: u32, resistances: Resistances) -> Health {
+        Health {
+            current: max,
+            max,
+            resistances,
+        }
+    }
+
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current == 0
+    }
+
+    /// Restores health, up to the max. The dead can't be healed.
+    pub fn heal(&mut self, amount: u32) {
+        if !self.is_dead() {
+            self.current = (self.current + amount).min(self.max);
+        }
+    }
+
+    /// Subtracts the damage, after resistances, from the current health.
+    /// Returns true only for the hit that takes health to zero, hitting something that is already dead returns false.
+    pub fn take_damage(&mut self, damage: Damage) -> bool {
+        if self.is_dead() {
+            return false;
+        }
+        self.current = self.current.saturating_sub(self.resistances.reduce(damage));
+        self.is_dead()
+    }
+}
+
+/// Emitted when an entity's health reaches zero.
+#[derive(Clone, Copy, Debug)]
+pub struct DeathEvent {
+    pub id: EntityId,
+    pub position: Vector2<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damage_without_a_resistance_is_taken_in_full() {
+        let mut health = Health::new(100, Resistances::default());
+        assert!(!health.take_damage(Damage::new(30, DamageKind::Vaccine)));
+        assert_eq!(health.current(), 70);
+    }
+
+    #[test]
+    fn resistances_only_reduce_their_own_kind() {
+        let resistances = Resistances::default().with(DamageKind::Vaccine, 0.25);
+        let mut health = Health::new(100, resistances);
+        health.take_damage(Damage::new(40, DamageKind::Vaccine));
+        assert_eq!(health.current(), 70);
+        health.take_damage(Damage::new(40, DamageKind::Contact));
+        assert_eq!(health.current(), 30);
+    }
+
+    #[test]
+    fn resistances_are_clamped() {
+        let immune = Resistances::default().with(DamageKind::Vaccine, 2.0);
+        assert_eq!(immune.reduce(Damage::new(50, DamageKind::Vaccine)), 0);
+        let negative = Resistances::default().with(DamageKind::Vaccine, -1.0);
+        assert_eq!(negative.reduce(Damage::new(50, DamageKind::Vaccine)), 50);
+    }
+
+    #[test]
+    fn only_the_killing_blow_reports_a_death() {
+        let mut health = Health::new(10, Resistances::default());
+        assert!(health.take_damage(Damage::new(25, DamageKind::Vaccine)));
+        assert_eq!(health.current(), 0);
+        assert!(!health.take_damage(Damage::new(25, DamageKind::Vaccine)));
+    }
+
+    #[test]
+    fn the_dead_can_not_be_healed() {
+        let mut health = Health::new(10, Resistances::default());
+        health.take_damage(Damage::new(4, DamageKind::Contact));
+        health.heal(100);
+        assert_eq!(health.current(), 10);
+        health.take_damage(Damage::new(10, DamageKind::Contact));
+        health.heal(5);
+        assert!(health.is_dead());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::game::text;
+use crate::game::world::World;
+use graphics::rectangle;
+use opengl_graphics::{GlGraphics, GlyphCache};
+use piston_window::Context;
+
+const HUD_TEXT_SIZE: u32 = 18;
+const HUD_MESSAGE_SIZE: u32 = 40;
+const HUD_TEXT_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
+// Distance between the edges of the view and the text in its corners
+const HUD_MARGIN: f64 = 20.0;
+const HUD_LINE_HEIGHT: f64 = 27.0;
+// Seconds a message stays up, it fades out over the last of them
+const MESSAGE_SECONDS: f64 = 3.0;
+const MESSAGE_FADE_SECONDS: f64 = 0.5;
+const BOSS_BAR_WIDTH: f64 = 500.0;
+const BOSS_BAR_HEIGHT: f64 = 16.0;
+// Distance from the top of the view to the top of the boss's health bar
+const BOSS_BAR_TOP: f64 = 40.0;
+const BOSS_BAR_COLOR: [f32; 4] = [0.8, 0.1, 0.1, 1.0];
+const BOSS_BAR_BACKGROUND: [f32; 4] = [0.2, 0.2, 0.2, 0.8];
+
+/// Hud is the information drawn in screen space over the world while it is being played.
+/// The player's health, weapon and effects are in the top left, the score, wave and combo in the top right,
+/// the boss's health along the top and messages about the game in the middle of the view.
+#[derive(Default)]
+pub struct Hud {
+    // The message being shown and how many seconds it has left
+    message: Option<(String, f64)>,
+}
+
+impl Hud {
+    pub fn new() -> Hud {
+        Hud::default()
+    }
+
+    /// Shows a message in the middle of the view for a few seconds, replacing whatever was there.
+    pub fn show(&mut self, message: String) {
+        self.message = Some((message, MESSAGE_SECONDS));
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.message = match self.message.take() {
+            Some((message, seconds_left)) if seconds_left > dt => {
+                Some((message, seconds_left - dt))
+            }
+            _ => None,
+        };
+    }
+
+    pub fn render(
+        &self,
+        world: &World,
+        glyphs: &mut GlyphCache,
+        context: Context,
+        graphics: &mut GlGraphics,
+    ) {
+        Hud::draw_player(world, glyphs, context, graphics);
+        Hud::draw_progress(world, glyphs, context, graphics);
+        Hud::draw_boss_health(world, glyphs, context, graphics);
+        self.draw_message(glyphs, context, graphics);
+    }
+
+    /// Health, the current weapon with its ammo, then every timed pickup effect.
+    fn draw_player(
+        world: &World,
+        glyphs: &mut GlyphCache,
+        context: Context,
+        graphics: &mut GlGraphics,
+    ) {
+        let health = world.player_health();
+        let mut lines = vec![format!("Health {}/{}", health.current(), health.max())];
+        if let Some(weapon) = world.weapon() {
+            if weapon.is_reloading() {
+                lines.push(format!("{} reloading", weapon.name()));
+            } else {
+                lines.push(format!(
+                    "{} {}/{}",
+                    weapon.name(),
+                    weapon.ammo(),
+                    weapon.magazine_size()
+                ));
+            }
+        }
+        for (effect, seconds_left) in world.active_effects() {
+            lines.push(format!("{} {:.1}s", effect.name(), seconds_left));
+        }
+
+        let mut y = HUD_MARGIN + f64::from(HUD_TEXT_SIZE);
+        for line in lines {
+            text::draw(
+                glyphs,
+                &line,
+                HUD_TEXT_SIZE,
+                HUD_TEXT_COLOR,
+                [HUD_MARGIN, y],
+                &context,
+                graphics,
+            );
+            y += HUD_LINE_HEIGHT;
+        }
+    }
+
+    /// Score, wave and the combo while one is going.
+    fn draw_progress(
+        world: &World,
+        glyphs: &mut GlyphCache,
+        context: Context,
+        graphics: &mut GlGraphics,
+    ) {
+        let score = world.score();
+        let mut lines = vec![
+            format!("Score {}", score.points()),
+            format!("Wave {}", world.current_wave()),
+        ];
+        if score.combo() > 1 {
+            lines.push(format!(
+                "Combo {} x{:.2}",
+                score.combo(),
+                score.multiplier()
+            ));
+        }
+
+        let [width, _] = context.get_view_size();
+        let mut y = HUD_MARGIN + f64::from(HUD_TEXT_SIZE);
+        for line in lines {
+            text::draw_right_aligned(
+                glyphs,
+                &line,
+                HUD_TEXT_SIZE,
+                HUD_TEXT_COLOR,
+                [width - HUD_MARGIN, y],
+                &context,
+                graphics,
+            );
+            y += HUD_LINE_HEIGHT;
+        }
+    }
+
+    /// Draws the boss's health bar across the top of the view while a boss is alive.
+    fn draw_boss_health(
+        world: &World,
+        glyphs: &mut GlyphCache,
+        context: Context,
+        graphics: &mut GlGraphics,
+    ) {
+        let health = match world.boss_health() {
+            Some(health) => health,
+            None => return,
+        };
+        let [width, _] = context.get_view_size();
+        let left = (width - BOSS_BAR_WIDTH) / 2.0;
+        let fraction = f64::from(health.current()) / f64::from(health.max());
+        rectangle(
+            BOSS_BAR_BACKGROUND,
+            [left, BOSS_BAR_TOP, BOSS_BAR_WIDTH, BOSS_BAR_HEIGHT],
+            context.transform,
+            graphics,
+        );
+        rectangle(
+            BOSS_BAR_COLOR,
+            [
+                left,
+                BOSS_BAR_TOP,
+                BOSS_BAR_WIDTH * fraction,
+                BOSS_BAR_HEIGHT,
+            ],
+            context.transform,
+            graphics,
+        );
+        text::draw_centered(
+            glyphs,
+            "Boss",
+            HUD_TEXT_SIZE,
+            HUD_TEXT_COLOR,
+            BOSS_BAR_TOP - 6.0,
+            &context,
+            graphics,
+        );
+    }
+
+    fn draw_message(&self, glyphs: &mut GlyphCache, context: Context, graphics: &mut GlGraphics) {
+        let (message, seconds_left) = match &self.message {
+            Some(message) => message,
+            None => return,
+        };
+        let mut color = HUD_TEXT_COLOR;
+        color[3] *= (seconds_left / MESSAGE_FADE_SECONDS).min(1.0) as f32;
+        let [_, height] = context.get_view_size();
+        text::draw_centered(
+            glyphs,
+            message,
+            HUD_MESSAGE_SIZE,
+            color,
+            height / 3.0,
+            &context,
+            graphics,
+        );
+    }
+}
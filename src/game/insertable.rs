@@ -1,10 +1,11 @@
+use crate::game::animation::{AnimationState, Animator};
+use crate::game::collision::layers::Layer;
 use nalgebra::Isometry2;
 use nphysics2d::object::{
     ColliderDesc, DefaultBodyHandle, DefaultBodySet, DefaultColliderHandle, RigidBody,
 };
 use opengl_graphics::Texture;
-use sprite::Scene;
-use std::rc::Rc;
+use sprite::{Scene, Sprite};
 use uuid::Uuid;
 
 // TODO: Should the actual "insert" methods exist in here? Or would that make the insertables know too much about the world they're being put into?
@@ -13,18 +14,21 @@ pub struct PhysicsInsertable {
     rigid_body: RigidBody<f64>,
     // This is optional in case something does not want to collide
     collider_desc: Option<ColliderDesc<f64>>,
+    layer: Layer,
 }
 
 /// Represents and object that can be inserted into the physics world.
 /// When inserted into the world a PhysicsInsertable should be returned to keep track of the objects in the physics world.
 impl PhysicsInsertable {
-    fn new(
+    pub fn new(
         rigid_body: RigidBody<f64>,
         collider_desc: Option<ColliderDesc<f64>>,
+        layer: Layer,
     ) -> PhysicsInsertable {
         PhysicsInsertable {
             rigid_body,
             collider_desc,
+            layer,
         }
     }
 
@@ -32,8 +36,8 @@ impl PhysicsInsertable {
         self.rigid_body.position()
     }
 
-    pub fn parts(self) -> (RigidBody<f64>, Option<ColliderDesc<f64>>) {
-        (self.rigid_body, self.collider_desc)
+    pub fn parts(self) -> (RigidBody<f64>, Option<ColliderDesc<f64>>, Layer) {
+        (self.rigid_body, self.collider_desc, self.layer)
     }
 }
 
@@ -57,24 +61,30 @@ impl PhysicsInserted {
     fn get_body_handle(&self) -> DefaultBodyHandle {
         self.body_handle
     }
+
+    pub fn get_collider_handle(&self) -> Option<DefaultColliderHandle> {
+        self.collider_handle
+    }
 }
 
 pub struct Insertable {
-    texture: Rc<Texture>,
+    texture_name: &'static str,
     physics_insertable: PhysicsInsertable,
 }
 
 /// Represents and object that can be inserted into the physics and sprite worlds.
+/// The texture is only referenced by name so that an Insertable can be built without an OpenGL context.
 /// When inserted into the world an Inserted should be returned to track the sprite and physics objects.
 impl Insertable {
     pub fn new(
-        texture: Rc<Texture>,
+        texture_name: &'static str,
         rigid_body: RigidBody<f64>,
         collider_desc: Option<ColliderDesc<f64>>,
+        layer: Layer,
     ) -> Insertable {
-        let physics_insertable = PhysicsInsertable::new(rigid_body, collider_desc);
+        let physics_insertable = PhysicsInsertable::new(rigid_body, collider_desc, layer);
         Insertable {
-            texture,
+            texture_name,
             physics_insertable,
         }
     }
@@ -83,55 +93,115 @@ impl Insertable {
         self.physics_insertable.rigid_body_position()
     }
 
-    pub fn get_parts(self) -> (Rc<Texture>, RigidBody<f64>, Option<ColliderDesc<f64>>) {
-        let (rigid_body, collider_desc) = self.physics_insertable.parts();
-        (self.texture, rigid_body, collider_desc)
-    }
-
-    pub fn get_parts_insertable(self) -> (Rc<Texture>, PhysicsInsertable) {
-        (self.texture, self.physics_insertable)
+    pub fn get_parts_insertable(self) -> (&'static str, PhysicsInsertable) {
+        (self.texture_name, self.physics_insertable)
     }
 }
 
 /// Represents an item that has been inserted into the physics and sprite worlds.
 /// When two colliders make contact, there should be a Uuid in the user_data of that collider which will point to an Inserted.
 /// Based on actions we need to take, we can apply affects to the Inserted by referencing its handles and sprite Uuid.
+/// The sprite is optional because a headless world never adds anything to the scene.
+/// Sprites whose texture has a sprite sheet also get an Animator to play its clips.
 pub struct Inserted {
-    sprite_uuid: Uuid,
+    texture_name: &'static str,
+    sprite_uuid: Option<Uuid>,
+    animator: Option<Animator>,
     physics_inserted: PhysicsInserted,
 }
 
 impl Inserted {
-    pub fn new(
-        sprite_uuid: Uuid,
-        body_handle: DefaultBodyHandle,
-        collider_handle: Option<DefaultColliderHandle>,
+    pub fn new_from_physics(
+        texture_name: &'static str,
+        physics_inserted: PhysicsInserted,
     ) -> Inserted {
-        let physics_inserted = PhysicsInserted::new(body_handle, collider_handle);
         Inserted {
-            sprite_uuid,
+            texture_name,
+            sprite_uuid: None,
+            animator: None,
             physics_inserted,
         }
     }
 
-    pub fn new_from_physics(sprite_uuid: Uuid, physics_inserted: PhysicsInserted) -> Inserted {
-        Inserted {
-            sprite_uuid,
-            physics_inserted,
-        }
+    pub fn get_texture_name(&self) -> &'static str {
+        self.texture_name
     }
 
-    pub fn get_sprite_uuid(&self) -> Uuid {
+    pub fn get_sprite_uuid(&self) -> Option<Uuid> {
         self.sprite_uuid
     }
 
+    pub fn set_sprite_uuid(&mut self, sprite_uuid: Uuid) {
+        self.sprite_uuid = Some(sprite_uuid);
+    }
+
+    pub fn set_animator(&mut self, animator: Animator) {
+        self.animator = Some(animator);
+    }
+
+    /// Takes the animator away, so it can keep playing after the rest of the Inserted is gone.
+    pub fn take_animator(&mut self) -> Option<Animator> {
+        self.animator.take()
+    }
+
+    /// Starts the clip for `state` without waiting for the next call to `animate`.
+    pub fn play_animation(&mut self, state: AnimationState) {
+        if let Some(animator) = self.animator.as_mut() {
+            animator.play(state);
+        }
+    }
+
+    /// Plays the clip for `state`, moves it forward by `dt` seconds and shows its current frame on the sprite.
+    pub fn animate(&mut self, scene: &mut Scene<Texture>, state: AnimationState, dt: f64) {
+        if let Some(animator) = self.animator.as_mut() {
+            animator.play(state);
+            animator.update(dt);
+            if let Some(sprite) = self.sprite_uuid.and_then(|uuid| scene.child_mut(uuid)) {
+                sprite.set_src_rect(animator.src_rect());
+            }
+        }
+    }
+
+    pub fn get_sprite_mut<'a>(
+        &self,
+        scene: &'a mut Scene<Texture>,
+    ) -> Option<&'a mut Sprite<Texture>> {
+        match self.sprite_uuid {
+            Some(sprite_uuid) => scene.child_mut(sprite_uuid),
+            None => None,
+        }
+    }
+
     pub fn get_body_handle(&self) -> DefaultBodyHandle {
         self.physics_inserted.body_handle
     }
+
+    pub fn get_collider_handle(&self) -> Option<DefaultColliderHandle> {
+        self.physics_inserted.collider_handle
+    }
+
+    /// Places the sprite where the rigid body will be `lookahead` seconds from now at its current velocity.
+    /// Used to smooth out rendering between fixed size physics steps.
+    pub fn extrapolate_sprite(
+        &self,
+        body_set: &DefaultBodySet<f64>,
+        scene: &mut Scene<Texture>,
+        lookahead: f64,
+    ) {
+        if let Some(sprite) = self.get_sprite_mut(scene) {
+            if let Some(rigid_body) = body_set.rigid_body(self.get_body_handle()) {
+                let position = rigid_body.position().translation.vector
+                    + rigid_body.velocity().linear * lookahead;
+                sprite.set_position(position[0], position[1]);
+            }
+        }
+    }
 }
 
 pub trait InsertedBody {
     fn update(&self, world: &DefaultBodySet<f64>, scene: &mut Scene<Texture>);
     fn get_body_handle(&self) -> DefaultBodyHandle;
-    fn get_sprite_uuid(&self) -> Uuid;
+    fn get_sprite_uuid(&self) -> Option<Uuid>;
+    fn inserted(&self) -> &Inserted;
+    fn inserted_mut(&mut self) -> &mut Inserted;
 }
@@ -0,0 +1,278 @@
+use crate::game::assets::{AssetError, Assets};
+use crate::game::level::tiles::TileMap;
+use crate::game::navigation::grid::Bounds;
+use nalgebra::{Isometry2, Point2, Vector2};
+use ncollide2d::shape::{Ball, Compound, ConvexPolygon, Cuboid, Polyline, ShapeHandle};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tiled::{Object, ObjectShape};
+
+mod polygon;
+pub mod tiles;
+
+// Objects with these types mark spawns, every other object in an object layer is level geometry
+const PLAYER_SPAWN_TYPE: &str = "player_spawn";
+const ENEMY_SPAWN_TYPE: &str = "enemy_spawn";
+
+#[derive(Debug)]
+pub enum LevelError {
+    Assets(AssetError),
+    /// The map file exists but could not be parsed.
+    InvalidMap {
+        path: PathBuf,
+        reason: String,
+    },
+    /// The map has no object with the player_spawn type.
+    MissingPlayerSpawn(PathBuf),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Assets(error) => write!(f, "{}", error),
+            LevelError::InvalidMap { path, reason } => {
+                write!(f, "could not load map {}: {}", path.display(), reason)
+            }
+            LevelError::MissingPlayerSpawn(path) => write!(
+                f,
+                "map {} has no {} object",
+                path.display(),
+                PLAYER_SPAWN_TYPE
+            ),
+        }
+    }
+}
+
+impl Error for LevelError {}
+
+impl From<AssetError> for LevelError {
+    fn from(error: AssetError) -> LevelError {
+        LevelError::Assets(error)
+    }
+}
+
+/// A rectangle that enemies can be spawned in.
+#[derive(Clone, Debug)]
+pub struct SpawnZone {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl SpawnZone {
+    /// Point objects and zero sized rectangles have no area to pick a spawn position from.
+    pub fn has_area(&self) -> bool {
+        self.width > 0.0 && self.height > 0.0
+    }
+}
+
+/// A piece of static level geometry, ready to be put on a static body.
+#[derive(Clone)]
+pub struct LevelCollider {
+    pub position: Isometry2<f64>,
+    pub shape: ShapeHandle<f64>,
+}
+
+/// Level is everything the game needs out of a Tiled map.
+/// Object layers give the static colliders and the spawns, tile layers are only drawn.
+/// Loading a level does not need an OpenGL context, the tile textures are loaded separately.
+#[derive(Clone)]
+pub struct Level {
+    bounds: Bounds,
+    player_spawn: Vector2<f64>,
+    spawn_zones: HashMap<String, SpawnZone>,
+    colliders: Vec<LevelCollider>,
+    tiles: TileMap,
+}
+
+impl Level {
+    /// Loads the map stored at `key`, which is its path relative to the assets folder.
+    pub fn load(key: &str) -> Result<Level, LevelError> {
+        let path = Assets::new()?.directory().join(key);
+        if !path.is_file() {
+            return Err(AssetError::MissingFile(path).into());
+        }
+        let map = tiled::parse_file(&path).map_err(|error| LevelError::InvalidMap {
+            path: path.clone(),
+            reason: format!("{:?}", error),
+        })?;
+
+        let mut player_spawn = None;
+        let mut spawn_zones = HashMap::new();
+        let mut colliders = vec![];
+        for object_group in &map.object_groups {
+            for object in &object_group.objects {
+                match object.obj_type.as_str() {
+                    PLAYER_SPAWN_TYPE => player_spawn = Some(Level::object_center(object)),
+                    ENEMY_SPAWN_TYPE => {
+                        spawn_zones.insert(object.name.clone(), Level::object_zone(object));
+                    }
+                    _ => match Level::object_collider(object) {
+                        Some(collider) => colliders.push(collider),
+                        None => println!(
+                            "Object {} in {} can't be used as a collider",
+                            object.id, key
+                        ),
+                    },
+                }
+            }
+        }
+
+        // Tileset images are relative to the map
+        let directory = Path::new(key).parent().unwrap_or_else(|| Path::new(""));
+        let tiles = TileMap::new(&map, directory);
+        Ok(Level {
+            bounds: Bounds {
+                x: 0.0,
+                y: 0.0,
+                width: f64::from(map.width * map.tile_width),
+                height: f64::from(map.height * map.tile_height),
+            },
+            player_spawn: player_spawn.ok_or(LevelError::MissingPlayerSpawn(path))?,
+            spawn_zones,
+            colliders,
+            tiles,
+        })
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    pub fn player_spawn(&self) -> Vector2<f64> {
+        self.player_spawn
+    }
+
+    /// The enemy spawn zones in the map, by their object names.
+    pub fn spawn_zones(&self) -> &HashMap<String, SpawnZone> {
+        &self.spawn_zones
+    }
+
+    pub fn colliders(&self) -> &[LevelCollider] {
+        &self.colliders
+    }
+
+    pub fn tiles(&self) -> &TileMap {
+        &self.tiles
+    }
+
+    // Tiled rotates objects clockwise in degrees around their top left corner
+    fn object_origin(object: &Object) -> Isometry2<f64> {
+        Isometry2::new(
+            Vector2::new(f64::from(object.x), f64::from(object.y)),
+            f64::from(object.rotation).to_radians(),
+        )
+    }
+
+    fn object_center(object: &Object) -> Vector2<f64> {
+        let center = Level::object_origin(object)
+            * Point2::new(
+                f64::from(object.width) / 2.0,
+                f64::from(object.height) / 2.0,
+            );
+        center.coords
+    }
+
+    // Spawn zones are axis aligned, so any rotation on them is ignored
+    fn object_zone(object: &Object) -> SpawnZone {
+        SpawnZone {
+            x: f64::from(object.x),
+            y: f64::from(object.y),
+            width: f64::from(object.width),
+            height: f64::from(object.height),
+        }
+    }
+
+    fn object_collider(object: &Object) -> Option<LevelCollider> {
+        let origin = Level::object_origin(object);
+        match &object.shape {
+            ObjectShape::Rect { width, height } => {
+                let half_extents = Vector2::new(f64::from(*width) / 2.0, f64::from(*height) / 2.0);
+                Some(LevelCollider {
+                    position: origin * Isometry2::translation(half_extents.x, half_extents.y),
+                    shape: ShapeHandle::new(Cuboid::new(half_extents)),
+                })
+            }
+            ObjectShape::Ellipse { width, height } => {
+                // ncollide has no ellipses, a circle that fits inside it is close enough for level geometry
+                let radius = f64::from(width.min(*height)) / 2.0;
+                Some(LevelCollider {
+                    position: origin
+                        * Isometry2::translation(f64::from(*width) / 2.0, f64::from(*height) / 2.0),
+                    shape: ShapeHandle::new(Ball::new(radius)),
+                })
+            }
+            ObjectShape::Polygon { points } => Level::polygon_shape(&Level::object_points(points))
+                .map(|shape| LevelCollider {
+                    position: origin,
+                    shape,
+                }),
+            ObjectShape::Polyline { points } => Some(LevelCollider {
+                position: origin,
+                shape: ShapeHandle::new(Polyline::new(Level::object_points(points), None)),
+            }),
+            ObjectShape::Point(_, _) => None,
+        }
+    }
+
+    /// ncollide only has convex polygons, so concave ones are cut into triangles that are stuck together.
+    /// Their convex hull would block off the space inside the dents.
+    fn polygon_shape(points: &[Point2<f64>]) -> Option<ShapeHandle<f64>> {
+        if polygon::is_convex(points) {
+            return ConvexPolygon::try_from_points(points).map(ShapeHandle::new);
+        }
+        let parts: Vec<(Isometry2<f64>, ShapeHandle<f64>)> = polygon::triangulate(points)?
+            .iter()
+            .filter_map(|triangle| ConvexPolygon::try_from_points(triangle))
+            .map(|triangle| (Isometry2::identity(), ShapeHandle::new(triangle)))
+            .collect();
+        if parts.is_empty() {
+            None
+        } else {
+            Some(ShapeHandle::new(Compound::new(parts)))
+        }
+    }
+
+    fn object_points(points: &[(f32, f32)]) -> Vec<Point2<f64>> {
+        points
+            .iter()
+            .map(|(x, y)| Point2::new(f64::from(*x), f64::from(*y)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convex_polygons_keep_a_single_shape() {
+        let square = Level::object_points(&[(0.0, 0.0), (32.0, 0.0), (32.0, 32.0), (0.0, 32.0)]);
+        let shape = Level::polygon_shape(&square).unwrap();
+        assert!(shape.as_shape::<ConvexPolygon<f64>>().is_some());
+    }
+
+    #[test]
+    fn concave_polygons_are_made_of_triangles() {
+        let l_shape = Level::object_points(&[
+            (0.0, 0.0),
+            (64.0, 0.0),
+            (64.0, 32.0),
+            (32.0, 32.0),
+            (32.0, 64.0),
+            (0.0, 64.0),
+        ]);
+        let shape = Level::polygon_shape(&l_shape).unwrap();
+        let compound = shape.as_shape::<Compound<f64>>().unwrap();
+        assert_eq!(compound.shapes().len(), 4);
+    }
+
+    #[test]
+    fn polygons_that_cross_themselves_are_not_colliders() {
+        let bow_tie = Level::object_points(&[(0.0, 0.0), (32.0, 32.0), (32.0, 0.0), (0.0, 32.0)]);
+        assert!(Level::polygon_shape(&bow_tie).is_none());
+    }
+}
@@ -0,0 +1,221 @@
+use nalgebra::Point2;
+
+// Turns smaller than this count as going straight on
+const STRAIGHT_EPSILON: f64 = 1.0e-9;
+
+/// Twice the signed area of the triangle abc. Which sign means a left turn depends on which way y points.
+fn turn(a: &Point2<f64>, b: &Point2<f64>, c: &Point2<f64>) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// True when `point`, already known to be in line with a and b, lies between them.
+fn within(a: &Point2<f64>, b: &Point2<f64>, point: &Point2<f64>) -> bool {
+    point.x >= a.x.min(b.x) - STRAIGHT_EPSILON
+        && point.x <= a.x.max(b.x) + STRAIGHT_EPSILON
+        && point.y >= a.y.min(b.y) - STRAIGHT_EPSILON
+        && point.y <= a.y.max(b.y) + STRAIGHT_EPSILON
+}
+
+/// True when the segments ab and cd cross or touch.
+fn segments_meet(a: &Point2<f64>, b: &Point2<f64>, c: &Point2<f64>, d: &Point2<f64>) -> bool {
+    let (a_side, b_side) = (turn(c, d, a), turn(c, d, b));
+    let (c_side, d_side) = (turn(a, b, c), turn(a, b, d));
+    let opposite = |first: f64, second: f64| {
+        (first > STRAIGHT_EPSILON && second < -STRAIGHT_EPSILON)
+            || (first < -STRAIGHT_EPSILON && second > STRAIGHT_EPSILON)
+    };
+    if opposite(a_side, b_side) && opposite(c_side, d_side) {
+        return true;
+    }
+    (a_side.abs() <= STRAIGHT_EPSILON && within(c, d, a))
+        || (b_side.abs() <= STRAIGHT_EPSILON && within(c, d, b))
+        || (c_side.abs() <= STRAIGHT_EPSILON && within(a, b, c))
+        || (d_side.abs() <= STRAIGHT_EPSILON && within(a, b, d))
+}
+
+/// True when no two edges of the polygon meet other than where neighbouring edges share a corner.
+fn is_simple(points: &[Point2<f64>]) -> bool {
+    let count = points.len();
+    let edge = |index: usize| (&points[index], &points[(index + 1) % count]);
+    (0..count).all(|first| {
+        // Neighbouring edges always share a corner, so only edges at least two along are checked
+        (first + 2..count)
+            .filter(|&second| (second + 1) % count != first)
+            .all(|second| {
+                let ((a, b), (c, d)) = (edge(first), edge(second));
+                !segments_meet(a, b, c, d)
+            })
+    })
+}
+
+/// True when the polygon doesn't cross itself and every corner turns the same way.
+/// Corners that go straight on don't count either way.
+pub fn is_convex(points: &[Point2<f64>]) -> bool {
+    if !is_simple(points) {
+        return false;
+    }
+    let count = points.len();
+    let mut direction = None;
+    for index in 0..count {
+        let turn = turn(
+            &points[index],
+            &points[(index + 1) % count],
+            &points[(index + 2) % count],
+        );
+        if turn.abs() <= STRAIGHT_EPSILON {
+            continue;
+        }
+        match direction {
+            None => direction = Some(turn > 0.0),
+            Some(left) if left != (turn > 0.0) => return false,
+            Some(_) => {}
+        }
+    }
+    true
+}
+
+/// Cuts a simple polygon into triangles by clipping ears off it one at a time.
+/// The points can go either way around. Returns None when the polygon crosses itself or has fewer than three corners.
+pub fn triangulate(points: &[Point2<f64>]) -> Option<Vec<[Point2<f64>; 3]>> {
+    if points.len() < 3 || !is_simple(points) {
+        return None;
+    }
+    let count = points.len();
+    let area: f64 = (0..count)
+        .map(|index| {
+            let (a, b) = (points[index], points[(index + 1) % count]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum();
+    let orientation = area.signum();
+
+    let mut remaining: Vec<usize> = (0..count).collect();
+    let mut triangles = vec![];
+    while remaining.len() > 3 {
+        let corners = remaining.len();
+        let corner = |index: usize| {
+            (
+                points[remaining[(index + corners - 1) % corners]],
+                points[remaining[index]],
+                points[remaining[(index + 1) % corners]],
+            )
+        };
+        // A corner that goes straight on adds no area, it can be dropped without a triangle
+        if let Some(straight) = (0..corners).find(|&index| {
+            let (a, b, c) = corner(index);
+            turn(&a, &b, &c).abs() <= STRAIGHT_EPSILON
+        }) {
+            remaining.remove(straight);
+            continue;
+        }
+        let ear = (0..corners).find(|&index| {
+            let (a, b, c) = corner(index);
+            turn(&a, &b, &c) * orientation > 0.0
+                && !remaining.iter().any(|&other| {
+                    let point = points[other];
+                    point != a
+                        && point != b
+                        && point != c
+                        && turn(&a, &b, &point) * orientation >= 0.0
+                        && turn(&b, &c, &point) * orientation >= 0.0
+                        && turn(&c, &a, &point) * orientation >= 0.0
+                })
+        })?;
+        let (a, b, c) = corner(ear);
+        triangles.push([a, b, c]);
+        remaining.remove(ear);
+    }
+    let last = [
+        points[remaining[0]],
+        points[remaining[1]],
+        points[remaining[2]],
+    ];
+    if turn(&last[0], &last[1], &last[2]).abs() > STRAIGHT_EPSILON {
+        triangles.push(last);
+    }
+    Some(triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coordinates: &[(f64, f64)]) -> Vec<Point2<f64>> {
+        coordinates
+            .iter()
+            .map(|&(x, y)| Point2::new(x, y))
+            .collect()
+    }
+
+    fn area(triangles: &[[Point2<f64>; 3]]) -> f64 {
+        triangles
+            .iter()
+            .map(|[a, b, c]| turn(a, b, c).abs() / 2.0)
+            .sum()
+    }
+
+    #[test]
+    fn squares_are_convex() {
+        let square = points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        assert!(is_convex(&square));
+        let triangles = triangulate(&square).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert!((area(&triangles) - 4.0).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn l_shapes_are_cut_into_triangles_that_cover_them() {
+        let l_shape = points(&[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 2.0),
+            (0.0, 2.0),
+        ]);
+        assert!(!is_convex(&l_shape));
+        let triangles = triangulate(&l_shape).unwrap();
+        assert_eq!(triangles.len(), 4);
+        assert!((area(&triangles) - 3.0).abs() < 1.0e-9);
+        // Every triangle turns the same way as the polygon, none of them cover the dent
+        let inside_dent = Point2::new(1.5, 1.5);
+        assert!(triangles.iter().all(|[a, b, c]| {
+            turn(a, b, &inside_dent) < 0.0
+                || turn(b, c, &inside_dent) < 0.0
+                || turn(c, a, &inside_dent) < 0.0
+        }));
+    }
+
+    #[test]
+    fn corners_that_go_straight_on_are_dropped() {
+        let square = points(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        assert!(is_convex(&square));
+        let triangles = triangulate(&square).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert!(triangles.iter().all(|[a, b, c]| turn(a, b, c).abs() > 0.0));
+        assert!((area(&triangles) - 4.0).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn polygons_that_cross_themselves_are_rejected() {
+        // A five pointed star drawn without lifting the pen, every corner turns the same way
+        let star: Vec<Point2<f64>> = [0, 2, 4, 1, 3]
+            .iter()
+            .map(|&corner| {
+                let angle = f64::from(corner) * 2.0 * std::f64::consts::PI / 5.0;
+                Point2::new(angle.cos(), angle.sin())
+            })
+            .collect();
+        assert!(!is_convex(&star));
+        assert!(triangulate(&star).is_none());
+
+        let bow_tie = points(&[(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)]);
+        assert!(!is_convex(&bow_tie));
+        assert!(triangulate(&bow_tie).is_none());
+    }
+
+    #[test]
+    fn fewer_than_three_corners_is_not_a_polygon() {
+        assert!(triangulate(&points(&[(0.0, 0.0), (1.0, 0.0)])).is_none());
+    }
+}
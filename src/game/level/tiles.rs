@@ -0,0 +1,136 @@
+use crate::game::assets::{AssetError, Assets};
+use graphics::{DrawState, Image};
+use opengl_graphics::{GlGraphics, Texture};
+use piston_window::math::Matrix2d;
+use std::path::Path;
+use std::rc::Rc;
+use tiled::{LayerData, Map};
+
+#[derive(Clone)]
+struct PlacedTile {
+    tileset: usize,
+    // Part of the tileset image to draw
+    source: [f64; 4],
+    // Where it is drawn in the world
+    destination: [f64; 4],
+}
+
+/// TileMap is a map's visible tile layers flattened into a list of tiles to draw, bottom layer first.
+/// Flipped tiles are drawn unflipped.
+#[derive(Clone)]
+pub struct TileMap {
+    tiles: Vec<PlacedTile>,
+    // The texture key for each tileset's image, tilesets made of separate images can't be drawn
+    tileset_images: Vec<Option<String>>,
+    // Only present once textures have been loaded
+    textures: Option<Vec<Option<Rc<Texture>>>>,
+}
+
+impl TileMap {
+    /// `directory` is the folder the map is in, relative to the assets folder.
+    pub fn new(map: &Map, directory: &Path) -> TileMap {
+        let tileset_images = map
+            .tilesets
+            .iter()
+            .map(|tileset| {
+                let image = tileset.images.first();
+                if image.is_none() {
+                    println!("Tileset {} has no single image to draw from", tileset.name);
+                }
+                image.map(|image| directory.join(&image.source).to_string_lossy().into_owned())
+            })
+            .collect();
+
+        let mut tiles = vec![];
+        for layer in map.layers.iter().filter(|layer| layer.visible) {
+            let rows = match &layer.tiles {
+                LayerData::Finite(rows) => rows,
+                LayerData::Infinite(_) => {
+                    println!(
+                        "Layer {} is infinite, only finite maps are drawn",
+                        layer.name
+                    );
+                    continue;
+                }
+            };
+            for (y, row) in rows.iter().enumerate() {
+                for (x, tile) in row.iter().enumerate() {
+                    // A gid of 0 is an empty cell
+                    if tile.gid == 0 {
+                        continue;
+                    }
+                    if let Some(placed_tile) = TileMap::place(map, tile.gid, x, y) {
+                        tiles.push(placed_tile);
+                    }
+                }
+            }
+        }
+
+        TileMap {
+            tiles,
+            tileset_images,
+            textures: None,
+        }
+    }
+
+    fn place(map: &Map, gid: u32, x: usize, y: usize) -> Option<PlacedTile> {
+        // A tile belongs to the last tileset that starts at or before its gid
+        let (index, tileset) = map
+            .tilesets
+            .iter()
+            .enumerate()
+            .filter(|(_, tileset)| tileset.first_gid <= gid)
+            .last()?;
+        let image = tileset.images.first()?;
+        let (tile_width, tile_height) = (tileset.tile_width, tileset.tile_height);
+        let columns = ((image.width.max(0) as u32).saturating_sub(2 * tileset.margin)
+            + tileset.spacing)
+            / (tile_width + tileset.spacing);
+        let id = gid - tileset.first_gid;
+        let column = id % columns.max(1);
+        let row = id / columns.max(1);
+        Some(PlacedTile {
+            tileset: index,
+            source: [
+                f64::from(tileset.margin + column * (tile_width + tileset.spacing)),
+                f64::from(tileset.margin + row * (tile_height + tileset.spacing)),
+                f64::from(tile_width),
+                f64::from(tile_height),
+            ],
+            // Tiles taller than the map's cells stick up out of the top of their cell, like they do in Tiled
+            destination: [
+                x as f64 * f64::from(map.tile_width),
+                (y + 1) as f64 * f64::from(map.tile_height) - f64::from(tile_height),
+                f64::from(tile_width),
+                f64::from(tile_height),
+            ],
+        })
+    }
+
+    /// Loads every tileset's image. This needs the OpenGL context, like any other texture.
+    pub fn load_textures(&mut self, assets: &mut Assets) -> Result<(), AssetError> {
+        let mut textures = vec![];
+        for key in &self.tileset_images {
+            textures.push(key.as_ref().map(|key| assets.texture(key)).transpose()?);
+        }
+        self.textures = Some(textures);
+        Ok(())
+    }
+
+    /// Draws every tile with `transform`, which should be the camera's. Nothing is drawn until textures are loaded.
+    pub fn render(&self, transform: Matrix2d, graphics: &mut GlGraphics) {
+        let textures = match &self.textures {
+            Some(textures) => textures,
+            None => return,
+        };
+        let draw_state = DrawState::default();
+        for tile in &self.tiles {
+            if let Some(texture) = &textures[tile.tileset] {
+                Image::new()
+                    .src_rect(tile.source)
+                    .rect(tile.destination)
+                    .draw(&**texture, &draw_state, transform, graphics);
+            }
+        }
+    }
+}
@@ -1,9 +1,25 @@
+pub mod animation;
+pub mod assets;
+pub mod audio;
+pub mod camera;
+pub mod collision;
+pub mod enemy;
+pub mod health;
+pub mod hud;
+pub mod level;
+pub mod score;
+pub mod state;
 pub mod view;
 pub mod world;
 
 mod bullet;
-mod enemy;
+mod entity;
 mod insertable;
+mod navigation;
+pub mod particles;
 mod physics_world;
+pub mod pickup;
 mod player;
+mod text;
 mod updatable;
+mod waves;
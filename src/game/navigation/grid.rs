@@ -0,0 +1,445 @@
+use nalgebra::Vector2;
+use ncollide2d::bounding_volume::AABB;
+use ncollide2d::shape::Compound;
+use nphysics2d::object::{DefaultBodySet, DefaultColliderSet};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+const STRAIGHT_COST: f64 = 1.0;
+const DIAGONAL_COST: f64 = std::f64::consts::SQRT_2;
+
+/// The area a NavGrid covers in world coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct Bounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Bounds {
+    pub fn contains(&self, point: Vector2<f64>) -> bool {
+        point.x >= self.x
+            && point.y >= self.y
+            && point.x <= self.x + self.width
+            && point.y <= self.y + self.height
+    }
+}
+
+type Cell = (usize, usize);
+
+/// NavGrid is a walkability grid built from the static colliders in the physics world.
+/// Paths are found on it with A* and then smoothed so enemies don't zig zag from cell to cell.
+pub struct NavGrid {
+    bounds: Bounds,
+    cell_size: f64,
+    columns: usize,
+    rows: usize,
+    blocked: Vec<bool>,
+}
+
+impl NavGrid {
+    /// Rasterizes every collider attached to a static body into the grid.
+    /// Colliders are grown by `clearance` so paths keep enemies' bodies from scraping along walls.
+    pub fn from_colliders(
+        body_set: &DefaultBodySet<f64>,
+        collider_set: &DefaultColliderSet<f64>,
+        bounds: Bounds,
+        cell_size: f64,
+        clearance: f64,
+    ) -> NavGrid {
+        let columns = (bounds.width / cell_size).ceil().max(1.0) as usize;
+        let rows = (bounds.height / cell_size).ceil().max(1.0) as usize;
+        let mut grid = NavGrid {
+            bounds,
+            cell_size,
+            columns,
+            rows,
+            blocked: vec![false; columns * rows],
+        };
+
+        for (_, collider) in collider_set.iter() {
+            let is_static = match body_set.get(collider.body()) {
+                Some(body) => body.is_static(),
+                None => false,
+            };
+            if !is_static {
+                continue;
+            }
+            // Concave level polygons are compounds of triangles, blocking each part on its own keeps the dents between them walkable
+            let aabbs: Vec<AABB<f64>> = match collider.shape().as_shape::<Compound<f64>>() {
+                Some(compound) => compound
+                    .shapes()
+                    .iter()
+                    .map(|(position, shape)| shape.aabb(&(collider.position() * position)))
+                    .collect(),
+                None => vec![collider.shape().aabb(collider.position())],
+            };
+            for aabb in aabbs {
+                grid.block_area(
+                    aabb.mins().x - clearance,
+                    aabb.mins().y - clearance,
+                    aabb.maxs().x + clearance,
+                    aabb.maxs().y + clearance,
+                );
+            }
+        }
+        grid
+    }
+
+    fn block_area(&mut self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) {
+        let (min_column, min_row) = self.clamped_cell(min_x, min_y);
+        let (max_column, max_row) = self.clamped_cell(max_x, max_y);
+        for row in min_row..=max_row {
+            for column in min_column..=max_column {
+                let index = self.index((column, row));
+                self.blocked[index] = true;
+            }
+        }
+    }
+
+    fn index(&self, (column, row): Cell) -> usize {
+        row * self.columns + column
+    }
+
+    fn clamped_cell(&self, x: f64, y: f64) -> Cell {
+        let column = ((x - self.bounds.x) / self.cell_size).floor();
+        let row = ((y - self.bounds.y) / self.cell_size).floor();
+        (
+            column.max(0.0).min((self.columns - 1) as f64) as usize,
+            row.max(0.0).min((self.rows - 1) as f64) as usize,
+        )
+    }
+
+    fn cell_center(&self, (column, row): Cell) -> Vector2<f64> {
+        Vector2::new(
+            self.bounds.x + (column as f64 + 0.5) * self.cell_size,
+            self.bounds.y + (row as f64 + 0.5) * self.cell_size,
+        )
+    }
+
+    fn is_walkable(&self, column: isize, row: isize) -> bool {
+        column >= 0
+            && row >= 0
+            && (column as usize) < self.columns
+            && (row as usize) < self.rows
+            && !self.blocked[self.index((column as usize, row as usize))]
+    }
+
+    /// The closest place to `point` that is clear of the level's colliders. That is `point` itself when it is
+    /// inside the grid on a walkable cell, otherwise the center of the nearest walkable cell.
+    /// Returns None when every cell is blocked.
+    pub fn nearest_walkable(&self, point: Vector2<f64>) -> Option<Vector2<f64>> {
+        let (column, row) = self.clamped_cell(point[0], point[1]);
+        if self.bounds.contains(point) && self.is_walkable(column as isize, row as isize) {
+            return Some(point);
+        }
+        (0..self.rows)
+            .flat_map(|row| (0..self.columns).map(move |column| (column, row)))
+            .filter(|&cell| !self.blocked[self.index(cell)])
+            .map(|cell| self.cell_center(cell))
+            .min_by(|a, b| {
+                (a - point)
+                    .norm()
+                    .partial_cmp(&(b - point).norm())
+                    .unwrap_or(Ordering::Equal)
+            })
+    }
+
+    /// Finds a path from `start` to `goal`, returned as a list of waypoints that ends at `goal`.
+    /// Returns None when the goal can't be reached.
+    pub fn find_path(&self, start: Vector2<f64>, goal: Vector2<f64>) -> Option<Vec<Vector2<f64>>> {
+        let start_cell = self.clamped_cell(start[0], start[1]);
+        let goal_cell = self.clamped_cell(goal[0], goal[1]);
+        let cells = self.a_star(start_cell, goal_cell)?;
+
+        let mut path = self.smooth(&cells);
+        path.push(goal);
+        Some(path)
+    }
+
+    fn a_star(&self, start: Cell, goal: Cell) -> Option<Vec<Cell>> {
+        if !self.is_walkable(goal.0 as isize, goal.1 as isize) {
+            return None;
+        }
+
+        let cell_count = self.columns * self.rows;
+        let mut cost_so_far = vec![std::f64::INFINITY; cell_count];
+        let mut came_from: Vec<Option<Cell>> = vec![None; cell_count];
+        // Cells are pushed again whenever a cheaper way to them is found, so the older entries have to be skipped
+        let mut closed = vec![false; cell_count];
+        let mut open = BinaryHeap::new();
+
+        cost_so_far[self.index(start)] = 0.0;
+        open.push(OpenCell {
+            cell: start,
+            estimated_cost: self.heuristic(start, goal),
+        });
+
+        while let Some(OpenCell { cell, .. }) = open.pop() {
+            if cell == goal {
+                return Some(self.reconstruct(&came_from, start, goal));
+            }
+            let cell_index = self.index(cell);
+            if closed[cell_index] {
+                continue;
+            }
+            closed[cell_index] = true;
+            let current_cost = cost_so_far[cell_index];
+            for (neighbour, step_cost) in self.neighbours(cell) {
+                let cost = current_cost + step_cost;
+                let neighbour_index = self.index(neighbour);
+                if cost < cost_so_far[neighbour_index] {
+                    cost_so_far[neighbour_index] = cost;
+                    came_from[neighbour_index] = Some(cell);
+                    open.push(OpenCell {
+                        cell: neighbour,
+                        estimated_cost: cost + self.heuristic(neighbour, goal),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Octile distance, the exact cost of the shortest path with no obstacles in the way.
+    fn heuristic(&self, (column, row): Cell, (goal_column, goal_row): Cell) -> f64 {
+        let dx = (column as f64 - goal_column as f64).abs();
+        let dy = (row as f64 - goal_row as f64).abs();
+        STRAIGHT_COST * (dx + dy) + (DIAGONAL_COST - 2.0 * STRAIGHT_COST) * dx.min(dy)
+    }
+
+    /// The walkable cells around `cell`. Diagonals are only allowed when they don't cut a blocked corner.
+    fn neighbours(&self, (column, row): Cell) -> Vec<(Cell, f64)> {
+        let (column, row) = (column as isize, row as isize);
+        let mut neighbours = Vec::with_capacity(8);
+        for &(dx, dy) in &[
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ] {
+            let (next_column, next_row) = (column + dx, row + dy);
+            if !self.is_walkable(next_column, next_row) {
+                continue;
+            }
+            let diagonal = dx != 0 && dy != 0;
+            if diagonal
+                && !(self.is_walkable(column + dx, row) && self.is_walkable(column, row + dy))
+            {
+                continue;
+            }
+            let cost = if diagonal {
+                DIAGONAL_COST
+            } else {
+                STRAIGHT_COST
+            };
+            neighbours.push(((next_column as usize, next_row as usize), cost));
+        }
+        neighbours
+    }
+
+    fn reconstruct(&self, came_from: &[Option<Cell>], start: Cell, goal: Cell) -> Vec<Cell> {
+        let mut cells = vec![goal];
+        let mut current = goal;
+        while current != start {
+            match came_from[self.index(current)] {
+                Some(previous) => {
+                    cells.push(previous);
+                    current = previous;
+                }
+                None => break,
+            }
+        }
+        cells.reverse();
+        cells
+    }
+
+    /// Drops every waypoint that can be skipped by walking in a straight line.
+    /// The first cell is where the enemy already is, so it is left out of the result.
+    fn smooth(&self, cells: &[Cell]) -> Vec<Vector2<f64>> {
+        let mut waypoints = vec![];
+        let mut anchor = 0;
+        while anchor + 1 < cells.len() {
+            let mut furthest = anchor + 1;
+            while furthest + 1 < cells.len()
+                && self.line_of_sight(cells[anchor], cells[furthest + 1])
+            {
+                furthest += 1;
+            }
+            waypoints.push(self.cell_center(cells[furthest]));
+            anchor = furthest;
+        }
+        waypoints
+    }
+
+    /// Walks every cell the line between two cell centers passes through and checks that none are blocked.
+    fn line_of_sight(&self, from: Cell, to: Cell) -> bool {
+        let (mut column, mut row) = (from.0 as isize, from.1 as isize);
+        let (end_column, end_row) = (to.0 as isize, to.1 as isize);
+        let dx = (end_column - column).abs();
+        let dy = (end_row - row).abs();
+        let step_x = if end_column > column { 1 } else { -1 };
+        let step_y = if end_row > row { 1 } else { -1 };
+        let mut error = dx - dy;
+
+        loop {
+            if !self.is_walkable(column, row) {
+                return false;
+            }
+            if column == end_column && row == end_row {
+                return true;
+            }
+            let doubled_error = 2 * error;
+            if doubled_error > -dy && doubled_error < dx {
+                // Moving diagonally, both cells beside the corner need to be clear
+                if !self.is_walkable(column + step_x, row)
+                    || !self.is_walkable(column, row + step_y)
+                {
+                    return false;
+                }
+            }
+            if doubled_error > -dy {
+                error -= dy;
+                column += step_x;
+            }
+            if doubled_error < dx {
+                error += dx;
+                row += step_y;
+            }
+        }
+    }
+}
+
+/// An entry in the A* open list, ordered so the BinaryHeap pops the lowest estimated cost first.
+struct OpenCell {
+    cell: Cell,
+    estimated_cost: f64,
+}
+
+impl PartialEq for OpenCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenCell {}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimated_cost
+            .partial_cmp(&self.estimated_cost)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An empty 10 by 10 grid of 10 unit cells
+    fn grid() -> NavGrid {
+        NavGrid::from_colliders(
+            &DefaultBodySet::new(),
+            &DefaultColliderSet::new(),
+            Bounds {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 100.0,
+            },
+            10.0,
+            0.0,
+        )
+    }
+
+    fn is_blocked(grid: &NavGrid, point: Vector2<f64>) -> bool {
+        let (column, row) = grid.clamped_cell(point.x, point.y);
+        !grid.is_walkable(column as isize, row as isize)
+    }
+
+    #[test]
+    fn open_ground_is_crossed_in_a_straight_line() {
+        let grid = grid();
+        let goal = Vector2::new(93.0, 97.0);
+        let path = grid.find_path(Vector2::new(5.0, 5.0), goal).unwrap();
+        assert_eq!(path, vec![Vector2::new(95.0, 95.0), goal]);
+    }
+
+    #[test]
+    fn paths_go_around_walls_through_the_gap() {
+        let mut grid = grid();
+        // A wall down column 4 with a gap in the bottom three rows
+        grid.block_area(40.0, 0.0, 49.0, 69.0);
+        let goal = Vector2::new(85.0, 15.0);
+        let path = grid.find_path(Vector2::new(15.0, 15.0), goal).unwrap();
+
+        assert_eq!(path.last(), Some(&goal));
+        assert!(path.iter().all(|&waypoint| !is_blocked(&grid, waypoint)));
+        assert!(path.iter().any(|waypoint| waypoint.y > 70.0));
+    }
+
+    #[test]
+    fn blocked_goals_cant_be_reached() {
+        let mut grid = grid();
+        grid.block_area(80.0, 80.0, 99.0, 99.0);
+        assert!(grid
+            .find_path(Vector2::new(5.0, 5.0), Vector2::new(95.0, 95.0))
+            .is_none());
+    }
+
+    #[test]
+    fn walled_in_goals_cant_be_reached() {
+        let mut grid = grid();
+        // Wall off the bottom right corner
+        grid.block_area(70.0, 70.0, 99.0, 79.0);
+        grid.block_area(70.0, 70.0, 79.0, 99.0);
+        assert!(grid
+            .find_path(Vector2::new(5.0, 5.0), Vector2::new(95.0, 95.0))
+            .is_none());
+    }
+
+    #[test]
+    fn walkable_points_are_their_own_nearest_walkable_point() {
+        let grid = grid();
+        let point = Vector2::new(42.0, 35.0);
+        assert_eq!(grid.nearest_walkable(point), Some(point));
+    }
+
+    #[test]
+    fn blocked_points_move_to_the_nearest_walkable_cell() {
+        let mut grid = grid();
+        grid.block_area(40.0, 0.0, 49.0, 69.0);
+        assert_eq!(
+            grid.nearest_walkable(Vector2::new(42.0, 35.0)),
+            Some(Vector2::new(35.0, 35.0))
+        );
+    }
+
+    #[test]
+    fn points_outside_the_grid_move_inside_it() {
+        let grid = grid();
+        assert_eq!(
+            grid.nearest_walkable(Vector2::new(-20.0, 55.0)),
+            Some(Vector2::new(5.0, 55.0))
+        );
+    }
+
+    #[test]
+    fn a_fully_blocked_grid_has_nowhere_walkable() {
+        let mut grid = grid();
+        grid.block_area(0.0, 0.0, 100.0, 100.0);
+        assert!(grid.nearest_walkable(Vector2::new(50.0, 50.0)).is_none());
+    }
+}
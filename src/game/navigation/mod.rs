@@ -0,0 +1,104 @@
+use crate::config::settings::Navigation as NavigationSettings;
+use crate::game::navigation::grid::{Bounds, NavGrid};
+use nalgebra::Vector2;
+use nphysics2d::object::{DefaultBodySet, DefaultColliderSet};
+
+pub mod grid;
+
+/// Navigation owns the walkability grid for the level and hands out PathFollowers that use it.
+pub struct Navigation {
+    grid: NavGrid,
+    replan_distance: f64,
+    waypoint_radius: f64,
+}
+
+impl Navigation {
+    pub fn new(
+        config: &NavigationSettings,
+        bounds: Bounds,
+        body_set: &DefaultBodySet<f64>,
+        collider_set: &DefaultColliderSet<f64>,
+    ) -> Navigation {
+        Navigation {
+            grid: NavGrid::from_colliders(
+                body_set,
+                collider_set,
+                bounds,
+                config.cell_size,
+                config.clearance,
+            ),
+            replan_distance: config.replan_distance,
+            waypoint_radius: config.waypoint_radius,
+        }
+    }
+
+    pub fn grid(&self) -> &NavGrid {
+        &self.grid
+    }
+
+    pub fn path_follower(&self) -> PathFollower {
+        PathFollower::new(self.replan_distance, self.waypoint_radius)
+    }
+}
+
+/// Where an enemy following a path should be heading.
+pub enum Heading {
+    /// A point along the way that should be passed through at full speed.
+    Waypoint(Vector2<f64>),
+    /// The end of the path, the enemy should slow down as it gets there.
+    Goal(Vector2<f64>),
+}
+
+/// PathFollower keeps track of an enemy's current path and which waypoint it is walking to.
+/// The path is only planned again once the goal has moved far enough from where it was planned to.
+pub struct PathFollower {
+    path: Vec<Vector2<f64>>,
+    next_waypoint: usize,
+    planned_goal: Option<Vector2<f64>>,
+    replan_distance: f64,
+    waypoint_radius: f64,
+}
+
+impl PathFollower {
+    pub fn new(replan_distance: f64, waypoint_radius: f64) -> PathFollower {
+        PathFollower {
+            path: vec![],
+            next_waypoint: 0,
+            planned_goal: None,
+            replan_distance,
+            waypoint_radius,
+        }
+    }
+
+    /// Works out where an enemy at `position` should head to reach `goal`.
+    /// If no path can be found the enemy heads straight for the goal.
+    pub fn heading(
+        &mut self,
+        navigation: &NavGrid,
+        position: Vector2<f64>,
+        goal: Vector2<f64>,
+    ) -> Heading {
+        let needs_plan = match self.planned_goal {
+            Some(planned_goal) => (goal - planned_goal).norm() > self.replan_distance,
+            None => true,
+        };
+        if needs_plan {
+            self.path = navigation.find_path(position, goal).unwrap_or_default();
+            self.next_waypoint = 0;
+            self.planned_goal = Some(goal);
+        }
+
+        while self.next_waypoint < self.path.len()
+            && (self.path[self.next_waypoint] - position).norm() < self.waypoint_radius
+        {
+            self.next_waypoint += 1;
+        }
+
+        // The last point of a path is where the goal was when it was planned, head for where it is now instead
+        if self.next_waypoint + 1 < self.path.len() {
+            Heading::Waypoint(self.path[self.next_waypoint])
+        } else {
+            Heading::Goal(goal)
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::config::settings::{Emitter as EmitterSettings, Particles as ParticleSettings};
+use crate::game::assets::{AssetError, Assets};
+use graphics::{rectangle, DrawState, Image};
+use nalgebra::Vector2;
+use opengl_graphics::{GlGraphics, Texture};
+use piston_window::math::Matrix2d;
+use rand::Rng;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Game events that play a particle effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleEvent {
+    /// A bullet left the player's weapon.
+    Fired,
+    /// A bullet hit an enemy.
+    Hit,
+    /// An enemy died.
+    Death,
+}
+
+struct Particle {
+    settings: Rc<EmitterSettings>,
+    position: Vector2<f64>,
+    velocity: Vector2<f64>,
+    age: f64,
+    lifetime: f64,
+}
+
+impl Particle {
+    /// How far through its life the particle is, from 0.0 to 1.0.
+    fn progress(&self) -> f64 {
+        (self.age / self.lifetime).min(1.0)
+    }
+
+    fn color(&self) -> [f32; 4] {
+        let progress = self.progress() as f32;
+        let mut color = self.settings.start_color;
+        for (channel, end) in color.iter_mut().zip(self.settings.end_color.iter()) {
+            *channel += (end - *channel) * progress;
+        }
+        color
+    }
+
+    fn size(&self) -> f64 {
+        let settings = &self.settings;
+        settings.start_size + (settings.end_size - settings.start_size) * self.progress()
+    }
+}
+
+/// An effect that is still letting out particles at its rate.
+struct ActiveEmitter {
+    settings: Rc<EmitterSettings>,
+    position: Vector2<f64>,
+    direction: Option<Vector2<f64>>,
+    time_left: f64,
+    // Particles owed from previous updates that did not add up to a whole one yet
+    pending: f64,
+}
+
+/// ParticleSystem plays the particle effects for game events. Particles are purely visual,
+/// they live outside of the physics world and never collide with anything.
+/// Like the World it is headless until textures are loaded, particles are simulated either way.
+pub struct ParticleSystem {
+    fired: Rc<EmitterSettings>,
+    hit: Rc<EmitterSettings>,
+    death: Rc<EmitterSettings>,
+    particles: Vec<Particle>,
+    emitters: Vec<ActiveEmitter>,
+    // Only present once textures have been loaded, by asset key
+    textures: Option<HashMap<String, Rc<Texture>>>,
+}
+
+impl ParticleSystem {
+    pub fn new(config: &ParticleSettings) -> ParticleSystem {
+        ParticleSystem {
+            fired: Rc::new(config.fired.clone()),
+            hit: Rc::new(config.hit.clone()),
+            death: Rc::new(config.death.clone()),
+            particles: vec![],
+            emitters: vec![],
+            textures: None,
+        }
+    }
+
+    /// Loads the texture of every effect that has one. This needs the OpenGL context, like any other texture.
+    pub fn load_textures(&mut self, assets: &mut Assets) -> Result<(), AssetError> {
+        let mut textures = HashMap::new();
+        for settings in &[&self.fired, &self.hit, &self.death] {
+            if let Some(key) = &settings.texture {
+                textures.insert(key.clone(), assets.texture(key)?);
+            }
+        }
+        self.textures = Some(textures);
+        Ok(())
+    }
+
+    /// Starts the effect for `event` at `position`. Particles fly out around `direction`, or in every direction without one.
+    pub fn emit(
+        &mut self,
+        event: ParticleEvent,
+        position: Vector2<f64>,
+        direction: Option<Vector2<f64>>,
+    ) {
+        let settings = match event {
+            ParticleEvent::Fired => Rc::clone(&self.fired),
+            ParticleEvent::Hit => Rc::clone(&self.hit),
+            ParticleEvent::Death => Rc::clone(&self.death),
+        };
+        for _ in 0..settings.burst {
+            self.spawn_particle(&settings, position, direction);
+        }
+        if settings.rate > 0.0 && settings.duration > 0.0 {
+            self.emitters.push(ActiveEmitter {
+                time_left: settings.duration,
+                settings,
+                position,
+                direction,
+                pending: 0.0,
+            });
+        }
+    }
+
+    fn spawn_particle(
+        &mut self,
+        settings: &Rc<EmitterSettings>,
+        position: Vector2<f64>,
+        direction: Option<Vector2<f64>>,
+    ) {
+        let mut rng = rand::thread_rng();
+        let (center, spread) = match direction {
+            Some(direction) => (
+                direction[1].atan2(direction[0]),
+                settings.spread.to_radians(),
+            ),
+            None => (0.0, std::f64::consts::PI * 2.0),
+        };
+        let angle = center + between(&mut rng, -spread / 2.0, spread / 2.0);
+        let speed = between(&mut rng, settings.min_speed, settings.max_speed);
+        self.particles.push(Particle {
+            settings: Rc::clone(settings),
+            position,
+            velocity: Vector2::new(angle.cos(), angle.sin()) * speed,
+            age: 0.0,
+            lifetime: between(&mut rng, settings.min_lifetime, settings.max_lifetime),
+        });
+    }
+
+    /// Moves every particle forward by `dt` seconds, lets the running effects emit and removes whatever has died.
+    pub fn update(&mut self, dt: f64) {
+        let mut emitters = std::mem::take(&mut self.emitters);
+        for emitter in emitters.iter_mut() {
+            let active = dt.min(emitter.time_left);
+            emitter.time_left -= dt;
+            emitter.pending += emitter.settings.rate * active;
+            while emitter.pending >= 1.0 {
+                emitter.pending -= 1.0;
+                self.spawn_particle(&emitter.settings, emitter.position, emitter.direction);
+            }
+        }
+        emitters.retain(|emitter| emitter.time_left > 0.0);
+        self.emitters = emitters;
+
+        for particle in self.particles.iter_mut() {
+            particle.age += dt;
+            particle.position += particle.velocity * dt;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    /// Draws every particle centered on its position with `transform`, which should be the camera's.
+    pub fn render(&self, transform: Matrix2d, graphics: &mut GlGraphics) {
+        let draw_state = DrawState::default();
+        for particle in &self.particles {
+            let size = particle.size();
+            let position = particle.position;
+            let rect = [
+                position[0] - size / 2.0,
+                position[1] - size / 2.0,
+                size,
+                size,
+            ];
+            let texture = match (&self.textures, &particle.settings.texture) {
+                (Some(textures), Some(key)) => textures.get(key),
+                _ => None,
+            };
+            match texture {
+                Some(texture) => Image::new_color(particle.color()).rect(rect).draw(
+                    &**texture,
+                    &draw_state,
+                    transform,
+                    graphics,
+                ),
+                None => rectangle(particle.color(), rect, transform, graphics),
+            }
+        }
+    }
+}
+
+/// A random number from `min` up to `max`, or `min` when there is no range to pick from.
+fn between<R: Rng>(rng: &mut R, min: f64, max: f64) -> f64 {
+    if max > min {
+        rng.gen_range(min, max)
+    } else {
+        min
+    }
+}
@@ -1,8 +1,12 @@
+use crate::config::settings::Settings;
+use crate::game::collision::layers::LayerMatrix;
 use crate::game::insertable::{PhysicsInsertable, PhysicsInserted};
 use nalgebra::Vector2;
 use nphysics2d::force_generator::DefaultForceGeneratorSet;
 use nphysics2d::joint::DefaultJointConstraintSet;
-use nphysics2d::object::{BodyPartHandle, DefaultBodySet, DefaultColliderSet};
+use nphysics2d::object::{
+    BodyPartHandle, DefaultBodyHandle, DefaultBodySet, DefaultColliderHandle, DefaultColliderSet,
+};
 use nphysics2d::world::{DefaultGeometricalWorld, DefaultMechanicalWorld};
 
 /// PhysicsWorld is a wrapper around the physics elements in nphysics.
@@ -13,11 +17,13 @@ pub struct PhysicsWorld {
     collider_set: DefaultColliderSet<f64>,
     force_set: DefaultForceGeneratorSet<f64>,
     joint_constraint_set: DefaultJointConstraintSet<f64>,
+    layers: LayerMatrix,
 }
 
 impl PhysicsWorld {
-    pub fn new() -> PhysicsWorld {
-        let mechanical_world = DefaultMechanicalWorld::new(Vector2::new(0.0, 0.0));
+    pub fn new(config: &Settings) -> PhysicsWorld {
+        let mut mechanical_world = DefaultMechanicalWorld::new(Vector2::new(0.0, 0.0));
+        mechanical_world.set_timestep(config.physics.timestep);
         let geometric_world = DefaultGeometricalWorld::new();
         let body_set: DefaultBodySet<f64> = DefaultBodySet::new();
         let collider_set: DefaultColliderSet<f64> = DefaultColliderSet::new();
@@ -31,6 +37,7 @@ impl PhysicsWorld {
             collider_set,
             force_set,
             joint_constraint_set,
+            layers: LayerMatrix::new(&config.collision),
         }
     }
 
@@ -62,16 +69,31 @@ impl PhysicsWorld {
         )
     }
 
+    /// Inserts the body and its collider. The collider is put into the collision groups for the insertable's layer.
     pub fn insert(&mut self, insertable: PhysicsInsertable) -> PhysicsInserted {
-        let (body_desc, collider_desc_option) = insertable.parts();
+        let (body_desc, collider_desc_option, layer) = insertable.parts();
         let inserted_handle = self.body_set.insert(body_desc);
         match collider_desc_option {
             Some(collider_desc) => {
-                let collider = collider_desc.build(BodyPartHandle(inserted_handle, 0));
+                let collider = collider_desc
+                    .collision_groups(self.layers.collision_groups(layer))
+                    .build(BodyPartHandle(inserted_handle, 0));
                 let collider_handle = self.collider_set.insert(collider);
                 PhysicsInserted::new(inserted_handle, Some(collider_handle))
             }
             None => PhysicsInserted::new(inserted_handle, None),
         }
     }
+
+    /// Removes a body and its collider from the physics world.
+    pub fn remove(
+        &mut self,
+        body_handle: DefaultBodyHandle,
+        collider_handle: Option<DefaultColliderHandle>,
+    ) {
+        if let Some(collider_handle) = collider_handle {
+            self.collider_set.remove(collider_handle);
+        }
+        self.body_set.remove(body_handle);
+    }
 }
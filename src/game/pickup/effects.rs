@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+/// Pickup effects that wear off after a while.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimedEffect {
+    FireRate,
+    Speed,
+    Invulnerability,
+}
+
+impl TimedEffect {
+    pub fn name(self) -> &'static str {
+        match self {
+            TimedEffect::FireRate => "Fire rate",
+            TimedEffect::Speed => "Speed",
+            TimedEffect::Invulnerability => "Invulnerable",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ActiveEffect {
+    remaining: f64,
+    // What the effect multiplies, for effects that have a strength
+    strength: f64,
+}
+
+/// The timed effects currently on the character.
+/// Picking up an effect that is already active refreshes it instead of stacking.
+#[derive(Debug, Default)]
+pub struct ActiveEffects {
+    effects: HashMap<TimedEffect, ActiveEffect>,
+}
+
+impl ActiveEffects {
+    pub fn add(&mut self, effect: TimedEffect, duration: f64, strength: f64) {
+        let remaining = self
+            .effects
+            .get(&effect)
+            .map_or(duration, |active| active.remaining.max(duration));
+        self.effects.insert(
+            effect,
+            ActiveEffect {
+                remaining,
+                strength,
+            },
+        );
+    }
+
+    /// Counts every effect down by `dt` seconds, removing the ones that run out.
+    pub fn update(&mut self, dt: f64) {
+        for active in self.effects.values_mut() {
+            active.remaining -= dt;
+        }
+        self.effects.retain(|_, active| active.remaining > 0.0);
+    }
+
+    pub fn is_active(&self, effect: TimedEffect) -> bool {
+        self.effects.contains_key(&effect)
+    }
+
+    /// The strength of an effect, or 1.0 if it isn't active so it can be used as a multiplier either way.
+    pub fn multiplier(&self, effect: TimedEffect) -> f64 {
+        self.effects
+            .get(&effect)
+            .map_or(1.0, |active| active.strength)
+    }
+
+    /// Every active effect with the seconds it has left, in a stable order for showing to the player.
+    pub fn remaining(&self) -> Vec<(TimedEffect, f64)> {
+        let mut remaining: Vec<(TimedEffect, f64)> = self
+            .effects
+            .iter()
+            .map(|(effect, active)| (*effect, active.remaining))
+            .collect();
+        remaining.sort_by_key(|(effect, _)| effect.name());
+        remaining
+    }
+}
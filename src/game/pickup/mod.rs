@@ -0,0 +1,150 @@
+use crate::game::collision::layers::Layer;
+use crate::game::insertable::{Insertable, Inserted, InsertedBody};
+use nalgebra::{Isometry2, Vector2};
+use ncollide2d::shape::{Ball, ShapeHandle};
+use nphysics2d::object::{
+    BodyStatus, ColliderDesc, DefaultBodyHandle, DefaultBodySet, RigidBodyDesc,
+};
+use opengl_graphics::Texture;
+use sprite::Scene;
+use std::convert::TryFrom;
+use uuid::Uuid;
+
+pub mod effects;
+
+const PICKUP_RADIUS: f64 = 15.0;
+// Pickups start fading out once they have this many seconds left
+const PICKUP_FADE_TIME: f64 = 3.0;
+
+/// The kinds of pickups enemies can drop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum PickupKind {
+    Health,
+    Ammo,
+    FireRate,
+    Speed,
+    Invulnerability,
+}
+
+impl PickupKind {
+    fn texture(self) -> &'static str {
+        match self {
+            PickupKind::Health => "pickups/health.png",
+            PickupKind::Ammo => "pickups/ammo.png",
+            PickupKind::FireRate => "pickups/fire_rate.png",
+            PickupKind::Speed => "pickups/speed.png",
+            PickupKind::Invulnerability => "pickups/invulnerability.png",
+        }
+    }
+}
+
+impl TryFrom<String> for PickupKind {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        match name.as_str() {
+            "health" => Ok(PickupKind::Health),
+            "ammo" => Ok(PickupKind::Ammo),
+            "fire_rate" => Ok(PickupKind::FireRate),
+            "speed" => Ok(PickupKind::Speed),
+            "invulnerability" => Ok(PickupKind::Invulnerability),
+            _ => Err(format!("unknown pickup {}", name)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PickupUserData {
+    pub uuid: Uuid,
+    kind: PickupKind,
+}
+
+impl PickupUserData {
+    /// Pickups sit still on a sensor collider, so the player walks over them instead of bumping into them.
+    pub fn generate_insertable(
+        kind: PickupKind,
+        position: Vector2<f64>,
+    ) -> (Insertable, PickupUserData) {
+        let user_data = PickupUserData {
+            uuid: Uuid::new_v4(),
+            kind,
+        };
+        let pickup_collider =
+            ColliderDesc::new(ShapeHandle::new(Ball::new(PICKUP_RADIUS))).sensor(true);
+        // Kinematic rather than static so the navigation grid never treats a pickup as level geometry
+        let pickup_body = RigidBodyDesc::new()
+            .status(BodyStatus::Kinematic)
+            .position(Isometry2::translation(position.x, position.y))
+            .user_data(user_data.clone())
+            .build();
+
+        (
+            Insertable::new(
+                kind.texture(),
+                pickup_body,
+                Some(pickup_collider),
+                Layer::Pickup,
+            ),
+            user_data,
+        )
+    }
+}
+
+pub struct InsertedPickup {
+    inserted: Inserted,
+    kind: PickupKind,
+    // Seconds left before the pickup disappears
+    lifetime_left: f64,
+}
+
+impl InsertedPickup {
+    pub fn new(inserted: Inserted, user_data: &PickupUserData, lifetime: f64) -> Self {
+        InsertedPickup {
+            inserted,
+            kind: user_data.kind,
+            lifetime_left: lifetime,
+        }
+    }
+
+    pub fn kind(&self) -> PickupKind {
+        self.kind
+    }
+
+    pub fn advance_age(&mut self, dt: f64) {
+        self.lifetime_left -= dt;
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.lifetime_left <= 0.0
+    }
+}
+
+impl InsertedBody for InsertedPickup {
+    fn update(&self, world: &DefaultBodySet<f64>, scene: &mut Scene<Texture>) {
+        if let Some(pickup_sprite) = self.inserted.get_sprite_mut(scene) {
+            if let Some(rigid_body) = world.rigid_body(self.inserted.get_body_handle()) {
+                let rigid_body_pos = rigid_body.position().translation.vector;
+                pickup_sprite.set_position(rigid_body_pos[0], rigid_body_pos[1]);
+            }
+            let opacity = (self.lifetime_left / PICKUP_FADE_TIME).max(0.0).min(1.0);
+            pickup_sprite.set_opacity(opacity as f32);
+        }
+    }
+
+    fn get_body_handle(&self) -> DefaultBodyHandle {
+        self.inserted.get_body_handle()
+    }
+
+    fn get_sprite_uuid(&self) -> Option<Uuid> {
+        self.inserted.get_sprite_uuid()
+    }
+
+    fn inserted(&self) -> &Inserted {
+        &self.inserted
+    }
+
+    fn inserted_mut(&mut self) -> &mut Inserted {
+        &mut self.inserted
+    }
+}
@@ -1,44 +1,59 @@
+use crate::config::settings::{Pickups, Player, Weapon as WeaponSettings};
+use crate::game::animation::hitbox::Hitboxes;
+use crate::game::collision::layers::Layer;
+use crate::game::health::{Damage, Health, Resistances};
+use crate::game::insertable::{Insertable, Inserted};
+use crate::game::pickup::effects::{ActiveEffects, TimedEffect};
+use crate::game::pickup::PickupKind;
+use crate::game::player::weapon::Weapon;
 use nalgebra::{Isometry2, Vector2};
-use ncollide2d::shape::{Cuboid, ShapeHandle};
 use nphysics2d::algebra::{Force2, ForceType};
 use nphysics2d::material::{BasicMaterial, MaterialHandle};
-use nphysics2d::object::{
-    Body, BodyPartHandle, ColliderDesc, DefaultBodyHandle, DefaultBodySet, DefaultColliderSet,
-    RigidBodyDesc,
-};
-use opengl_graphics::{Texture, TextureSettings};
+use nphysics2d::object::{Body, ColliderDesc, DefaultBodySet, RigidBodyDesc};
+use opengl_graphics::Texture;
 use piston_window::Key;
-use sprite::{Scene, Sprite};
+use sprite::Scene;
 use std::collections::HashSet;
-use std::rc::Rc;
-use uuid::Uuid;
 
 const CHARACTER_BODY_WIDTH: f64 = 20.0;
 const CHARACTER_BODY_HEIGHT: f64 = 20.0;
 const CHARACTER_SPEED: f64 = 3.0;
+const CHARACTER_TEXTURE: &str = "player.png";
+const INVULNERABLE_OPACITY: f32 = 0.4;
 
 pub struct Character {
     // It is possible to get the body handle from the collider handle following the example below
     //      Assuming `collider_handle` is a valid handle of a collider previously added to the world.
     //      let collider = collider_set.get(collider_handle).expect("Collider not found.");
     //      let body_handle = collider.body();
-    body_handle: DefaultBodyHandle,
-    //    collider_handle: DefaultColliderHandle,
-    //    player_image: Texture,
-    sprite_uuid: Uuid,
+    inserted: Inserted,
+    health: Health,
+    invulnerability_seconds: f64,
+    // Time left before the character can be hurt again
+    invulnerable_for: f64,
+    weapons: Vec<Weapon>,
+    current_weapon: usize,
+    effects: ActiveEffects,
 }
 
 impl Character {
-    pub fn new(
-        body_set: &mut DefaultBodySet<f64>,
-        collider_set: &mut DefaultColliderSet<f64>,
-        initial_position: (f64, f64),
-        scene: &mut Scene<Texture>,
-    ) -> Character {
-        let character_shape = ShapeHandle::new(Cuboid::new(Vector2::new(
-            CHARACTER_BODY_WIDTH,
-            CHARACTER_BODY_HEIGHT,
-        )));
+    pub fn new(inserted: Inserted, config: &Player, weapons: &[WeaponSettings]) -> Character {
+        Character {
+            inserted,
+            health: Health::new(config.health, Resistances::default()),
+            invulnerability_seconds: config.invulnerability_seconds,
+            invulnerable_for: 0.0,
+            weapons: weapons.iter().map(Weapon::new).collect(),
+            current_weapon: 0,
+            effects: ActiveEffects::default(),
+        }
+    }
+
+    pub fn generate_insertable(initial_position: (f64, f64), hitboxes: &Hitboxes) -> Insertable {
+        let character_shape = hitboxes.shape_or_box(
+            CHARACTER_TEXTURE,
+            Vector2::new(CHARACTER_BODY_WIDTH, CHARACTER_BODY_HEIGHT),
+        );
 
         let character_collider = ColliderDesc::new(character_shape)
             .density(1.0)
@@ -52,31 +67,98 @@ impl Character {
             .linear_damping(1.0)
             .build();
 
-        let body_handle = body_set.insert(character_body);
+        Insertable::new(
+            CHARACTER_TEXTURE,
+            character_body,
+            Some(character_collider),
+            Layer::Player,
+        )
+    }
 
-        let character_collider = character_collider.build(BodyPartHandle(body_handle, 0));
-        let _collider_handle = collider_set.insert(character_collider);
+    pub fn inserted(&self) -> &Inserted {
+        &self.inserted
+    }
 
-        let sprite_uuid = Character::generate_sprite(scene, initial_position);
+    pub fn inserted_mut(&mut self) -> &mut Inserted {
+        &mut self.inserted
+    }
 
-        Character {
-            body_handle,
-            //            collider_handle,
-            sprite_uuid,
+    pub fn health(&self) -> &Health {
+        &self.health
+    }
+
+    /// The weapon currently being held. This is only None if no weapons are configured.
+    pub fn weapon(&self) -> Option<&Weapon> {
+        self.weapons.get(self.current_weapon)
+    }
+
+    pub fn weapon_mut(&mut self) -> Option<&mut Weapon> {
+        self.weapons.get_mut(self.current_weapon)
+    }
+
+    /// Switches to the weapon at `index`, doing nothing if there isn't one there.
+    pub fn select_weapon(&mut self, index: usize) {
+        if index < self.weapons.len() {
+            self.current_weapon = index;
+        }
+    }
+
+    /// Switches `step` weapons forwards (or backwards if negative), wrapping around at either end.
+    pub fn cycle_weapon(&mut self, step: isize) {
+        let count = self.weapons.len() as isize;
+        if count > 0 {
+            let index = (self.current_weapon as isize + step).rem_euclid(count);
+            self.current_weapon = index as usize;
         }
     }
 
-    fn generate_sprite(scene: &mut Scene<Texture>, position: (f64, f64)) -> Uuid {
-        let assets = find_folder::Search::ParentsThenKids(3, 3)
-            .for_folder("assets")
-            .unwrap();
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable_for > 0.0 || self.effects.is_active(TimedEffect::Invulnerability)
+    }
 
-        let tex: Rc<Texture> = Rc::new(
-            Texture::from_path(assets.join("player.png"), &TextureSettings::new()).unwrap(),
-        );
-        let mut sprite = Sprite::from_texture(tex);
-        sprite.set_position(position.0, position.1);
-        scene.add_child(sprite)
+    pub fn effects(&self) -> &ActiveEffects {
+        &self.effects
+    }
+
+    /// Grants whatever a pickup gives, timed effects last as long as the config says.
+    pub fn apply_pickup(&mut self, kind: PickupKind, config: &Pickups) {
+        match kind {
+            PickupKind::Health => self.health.heal(config.health),
+            PickupKind::Ammo => {
+                for weapon in self.weapons.iter_mut() {
+                    weapon.refill();
+                }
+            }
+            PickupKind::FireRate => self.effects.add(
+                TimedEffect::FireRate,
+                config.boost_duration,
+                config.fire_rate_multiplier,
+            ),
+            PickupKind::Speed => self.effects.add(
+                TimedEffect::Speed,
+                config.boost_duration,
+                config.speed_multiplier,
+            ),
+            PickupKind::Invulnerability => self.effects.add(
+                TimedEffect::Invulnerability,
+                config.invulnerability_duration,
+                1.0,
+            ),
+        }
+    }
+
+    fn speed(&self) -> f64 {
+        CHARACTER_SPEED * self.effects.multiplier(TimedEffect::Speed)
+    }
+
+    /// Deals damage to the character unless it was hurt too recently.
+    /// Every hit that lands starts a new invulnerability window. Returns true if the hit killed the character.
+    pub fn hurt(&mut self, damage: Damage) -> bool {
+        if self.is_invulnerable() || self.health.is_dead() {
+            return false;
+        }
+        self.invulnerable_for = self.invulnerability_seconds;
+        self.health.take_damage(damage)
     }
 
     pub fn update(
@@ -84,7 +166,16 @@ impl Character {
         world: &mut DefaultBodySet<f64>,
         keys_pressed: &HashSet<Key>,
         scene: &mut Scene<Texture>,
+        dt: f64,
     ) {
+        self.invulnerable_for = (self.invulnerable_for - dt).max(0.0);
+        self.effects.update(dt);
+        // Every weapon keeps cooling down and reloading while it is put away
+        let fire_rate = self.effects.multiplier(TimedEffect::FireRate);
+        for weapon in self.weapons.iter_mut() {
+            weapon.update(dt, fire_rate);
+        }
+
         if keys_pressed.contains(&Key::W) {
             self.move_up(world);
         }
@@ -98,8 +189,8 @@ impl Character {
             self.move_down(world);
         }
 
-        if let Some(char_sprite) = scene.child_mut(self.sprite_uuid) {
-            if let Some(rigid_body) = world.rigid_body(self.body_handle) {
+        if let Some(char_sprite) = self.inserted.get_sprite_mut(scene) {
+            if let Some(rigid_body) = world.rigid_body(self.inserted.get_body_handle()) {
                 let rigid_body_pos = rigid_body.position().translation.vector;
                 let (x_pos, y_pos) = (rigid_body_pos[0], rigid_body_pos[1]);
                 char_sprite.set_position(x_pos, y_pos);
@@ -109,12 +200,17 @@ impl Character {
                 let rotation = char_rotation * 57.29578;
                 char_sprite.set_rotation(rotation);
             }
+            if self.is_invulnerable() {
+                char_sprite.set_opacity(INVULNERABLE_OPACITY);
+            } else {
+                char_sprite.set_opacity(1.0);
+            }
         }
     }
 
     pub fn update_rotation(&mut self, mouse_position: [f64; 2], world: &mut DefaultBodySet<f64>) {
         use std::f64;
-        if let Some(character_body) = world.rigid_body_mut(self.body_handle) {
+        if let Some(character_body) = world.rigid_body_mut(self.inserted.get_body_handle()) {
             let position = character_body.position().translation.vector;
             let (char_x_pos, char_y_pos) = (position[0], position[1]);
             let [mouse_x_pos, mouse_y_pos] = mouse_position;
@@ -135,7 +231,7 @@ impl Character {
     }
 
     pub fn get_rotation(&self, world: &DefaultBodySet<f64>) -> f64 {
-        if let Some(character_body) = world.rigid_body(self.body_handle) {
+        if let Some(character_body) = world.rigid_body(self.inserted.get_body_handle()) {
             character_body.position().rotation.angle()
         } else {
             // passing a default (we should never his this case)
@@ -145,7 +241,7 @@ impl Character {
     }
 
     pub fn get_position(&self, world: &DefaultBodySet<f64>) -> Vector2<f64> {
-        if let Some(character_body) = world.rigid_body(self.body_handle) {
+        if let Some(character_body) = world.rigid_body(self.inserted.get_body_handle()) {
             character_body.position().translation.vector
         } else {
             // passing a default (we should never his this case)
@@ -155,29 +251,29 @@ impl Character {
     }
 
     fn move_left(&self, world: &mut DefaultBodySet<f64>) {
-        if let Some(body) = world.rigid_body_mut(self.body_handle) {
-            let force = Force2::linear(Vector2::new(-CHARACTER_SPEED, 0.0));
+        if let Some(body) = world.rigid_body_mut(self.inserted.get_body_handle()) {
+            let force = Force2::linear(Vector2::new(-self.speed(), 0.0));
             body.apply_force(0, &force, ForceType::VelocityChange, false);
         }
     }
 
     fn move_right(&self, world: &mut DefaultBodySet<f64>) {
-        if let Some(body) = world.rigid_body_mut(self.body_handle) {
-            let force = Force2::linear(Vector2::new(CHARACTER_SPEED, 0.0));
+        if let Some(body) = world.rigid_body_mut(self.inserted.get_body_handle()) {
+            let force = Force2::linear(Vector2::new(self.speed(), 0.0));
             body.apply_force(0, &force, ForceType::VelocityChange, false);
         }
     }
 
     fn move_down(&self, world: &mut DefaultBodySet<f64>) {
-        if let Some(body) = world.rigid_body_mut(self.body_handle) {
-            let force: Force2<f64> = Force2::linear(Vector2::new(0.0, CHARACTER_SPEED));
+        if let Some(body) = world.rigid_body_mut(self.inserted.get_body_handle()) {
+            let force: Force2<f64> = Force2::linear(Vector2::new(0.0, self.speed()));
             body.apply_force(0, &force, ForceType::VelocityChange, false);
         }
     }
 
     fn move_up(&self, world: &mut DefaultBodySet<f64>) {
-        if let Some(body) = world.rigid_body_mut(self.body_handle) {
-            let jump_force = Force2::linear(Vector2::new(0.0, -CHARACTER_SPEED));
+        if let Some(body) = world.rigid_body_mut(self.inserted.get_body_handle()) {
+            let jump_force = Force2::linear(Vector2::new(0.0, -self.speed()));
             body.apply_force(0, &jump_force, ForceType::VelocityChange, false);
         }
     }
@@ -1 +1,2 @@
 pub mod character;
+pub mod weapon;
@@ -0,0 +1,205 @@
+use crate::config::settings::Weapon as WeaponSettings;
+use rand::Rng;
+
+/// Weapon keeps track of one of the character's weapons: its cooldown between shots, the rounds left in its magazine and its reload.
+/// It only decides whether a shot happens and in which directions, the World spawns the projectiles.
+pub struct Weapon {
+    stats: WeaponSettings,
+    ammo: u32,
+    // Time left before the weapon can fire again
+    cooldown_left: f64,
+    // Time left on the current reload, if there is one
+    reload_left: Option<f64>,
+}
+
+impl Weapon {
+    pub fn new(stats: &WeaponSettings) -> Weapon {
+        Weapon {
+            stats: stats.clone(),
+            ammo: stats.magazine_size,
+            cooldown_left: 0.0,
+            reload_left: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.stats.name
+    }
+
+    pub fn ammo(&self) -> u32 {
+        self.ammo
+    }
+
+    pub fn magazine_size(&self) -> u32 {
+        self.stats.magazine_size
+    }
+
+    pub fn is_automatic(&self) -> bool {
+        self.stats.automatic
+    }
+
+    pub fn is_reloading(&self) -> bool {
+        self.reload_left.is_some()
+    }
+
+    pub fn projectile_speed(&self) -> f64 {
+        self.stats.projectile_speed
+    }
+
+    pub fn damage(&self) -> u32 {
+        self.stats.damage
+    }
+
+    /// Moves the cooldown and any reload forward by `dt` seconds.
+    /// The cooldown runs `fire_rate` times faster than normal, reloads are not affected.
+    pub fn update(&mut self, dt: f64, fire_rate: f64) {
+        self.cooldown_left = (self.cooldown_left - dt * fire_rate).max(0.0);
+        if let Some(reload_left) = self.reload_left {
+            let reload_left = reload_left - dt;
+            if reload_left <= 0.0 {
+                self.ammo = self.stats.magazine_size;
+                self.reload_left = None;
+            } else {
+                self.reload_left = Some(reload_left);
+            }
+        }
+    }
+
+    /// Fills the magazine straight away, cancelling any reload.
+    pub fn refill(&mut self) {
+        self.ammo = self.stats.magazine_size;
+        self.reload_left = None;
+    }
+
+    /// Starts reloading unless the magazine is already full or a reload is already going.
+    pub fn reload(&mut self) {
+        if self.ammo < self.stats.magazine_size && !self.is_reloading() {
+            self.reload_left = Some(self.stats.reload_time);
+        }
+    }
+
+    /// Tries to fire the weapon while aiming at `rotation` (in radians).
+    /// Returns the rotation of every projectile in the shot, or None if the weapon can't fire right now.
+    /// Emptying the magazine starts a reload straight away.
+    pub fn fire(&mut self, rotation: f64) -> Option<Vec<f64>> {
+        if self.is_reloading() || self.cooldown_left > 0.0 {
+            return None;
+        }
+        if self.ammo == 0 {
+            self.reload();
+            return None;
+        }
+        self.ammo -= 1;
+        self.cooldown_left = self.stats.cooldown;
+        if self.ammo == 0 {
+            self.reload();
+        }
+        Some(self.spread(rotation))
+    }
+
+    // Several projectiles are fanned out evenly across the spread, a single one is fired somewhere random inside it
+    fn spread(&self, rotation: f64) -> Vec<f64> {
+        let spread = self.stats.spread.to_radians();
+        let count = self.stats.projectile_count;
+        if count <= 1 {
+            if spread <= 0.0 {
+                return vec![rotation];
+            }
+            let offset = rand::thread_rng().gen_range(-spread / 2.0, spread / 2.0);
+            return vec![rotation + offset];
+        }
+        let step = spread / f64::from(count - 1);
+        (0..count)
+            .map(|index| rotation - spread / 2.0 + step * f64::from(index))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(magazine_size: u32, projectile_count: u32, spread: f64) -> WeaponSettings {
+        WeaponSettings {
+            name: String::from("Test"),
+            cooldown: 0.5,
+            magazine_size,
+            reload_time: 1.0,
+            projectile_count,
+            spread,
+            projectile_speed: 100.0,
+            damage: 10,
+            automatic: false,
+        }
+    }
+
+    #[test]
+    fn firing_uses_a_round_and_waits_for_the_cooldown() {
+        let mut weapon = Weapon::new(&stats(3, 1, 0.0));
+        assert_eq!(weapon.fire(1.0), Some(vec![1.0]));
+        assert_eq!(weapon.ammo(), 2);
+        assert_eq!(weapon.fire(1.0), None);
+        weapon.update(0.25, 1.0);
+        assert_eq!(weapon.fire(1.0), None);
+        weapon.update(0.25, 1.0);
+        assert!(weapon.fire(1.0).is_some());
+        assert_eq!(weapon.ammo(), 1);
+    }
+
+    #[test]
+    fn fire_rate_speeds_up_the_cooldown() {
+        let mut weapon = Weapon::new(&stats(3, 1, 0.0));
+        weapon.fire(0.0);
+        weapon.update(0.25, 2.0);
+        assert!(weapon.fire(0.0).is_some());
+    }
+
+    #[test]
+    fn emptying_the_magazine_reloads_it() {
+        let mut weapon = Weapon::new(&stats(1, 1, 0.0));
+        assert!(weapon.fire(0.0).is_some());
+        assert_eq!(weapon.ammo(), 0);
+        assert!(weapon.is_reloading());
+        weapon.update(0.5, 1.0);
+        assert_eq!(weapon.fire(0.0), None);
+        weapon.update(0.5, 1.0);
+        assert!(!weapon.is_reloading());
+        assert_eq!(weapon.ammo(), 1);
+        assert!(weapon.fire(0.0).is_some());
+    }
+
+    #[test]
+    fn a_full_magazine_is_not_reloaded() {
+        let mut weapon = Weapon::new(&stats(2, 1, 0.0));
+        weapon.reload();
+        assert!(!weapon.is_reloading());
+        weapon.fire(0.0);
+        weapon.reload();
+        assert!(weapon.is_reloading());
+        weapon.refill();
+        assert!(!weapon.is_reloading());
+        assert_eq!(weapon.ammo(), 2);
+    }
+
+    #[test]
+    fn several_projectiles_are_fanned_out_across_the_spread() {
+        let mut weapon = Weapon::new(&stats(1, 3, 20.0));
+        let rotations = weapon.fire(0.0).unwrap();
+        let expected = [-10.0_f64, 0.0, 10.0];
+        assert_eq!(rotations.len(), expected.len());
+        for (rotation, degrees) in rotations.iter().zip(expected.iter()) {
+            assert!((rotation - degrees.to_radians()).abs() < 1.0e-9);
+        }
+    }
+
+    #[test]
+    fn a_single_projectile_lands_inside_the_spread() {
+        let half_spread = 15.0_f64.to_radians();
+        for _ in 0..20 {
+            let mut weapon = Weapon::new(&stats(1, 1, 30.0));
+            let rotations = weapon.fire(1.0).unwrap();
+            assert_eq!(rotations.len(), 1);
+            assert!((rotations[0] - 1.0).abs() <= half_spread);
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HIGH_SCORE_FILE: &str = "high_scores.json";
+
+#[derive(Debug)]
+pub enum HighScoreError {
+    /// The high score file could not be read or written.
+    Io { path: PathBuf, error: io::Error },
+    /// The high score file exists but is not a valid high score table.
+    Parse {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+    /// The high score table could not be turned into json to be saved.
+    Serialize {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+}
+
+impl fmt::Display for HighScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HighScoreError::Io { path, error } => {
+                write!(f, "could not access {}: {}", path.display(), error)
+            }
+            HighScoreError::Parse { path, error } => {
+                write!(f, "could not parse {}: {}", path.display(), error)
+            }
+            HighScoreError::Serialize { path, error } => {
+                write!(f, "could not serialize {}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl Error for HighScoreError {}
+
+/// A single finished game.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: u64,
+    pub wave: usize,
+    /// Seconds since the unix epoch when the game ended.
+    pub timestamp: u64,
+}
+
+impl HighScore {
+    /// A high score for a game that ended just now.
+    pub fn now(score: u64, wave: usize) -> HighScore {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        HighScore {
+            score,
+            wave,
+            timestamp,
+        }
+    }
+}
+
+/// The best scores so far, highest first. Stored as json in the data directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Where the high score table lives inside `data_dir`.
+    pub fn path(data_dir: &str) -> PathBuf {
+        Path::new(data_dir).join(HIGH_SCORE_FILE)
+    }
+
+    /// Reads the table from `path`. A missing file is an empty table, since nothing has been played yet.
+    pub fn load(path: &Path) -> Result<HighScores, HighScoreError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(HighScores::default())
+            }
+            Err(error) => {
+                return Err(HighScoreError::Io {
+                    path: path.to_path_buf(),
+                    error,
+                })
+            }
+        };
+        serde_json::from_str(&contents).map_err(|error| HighScoreError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+
+    /// Writes the table to `path`, creating the data directory if it doesn't exist yet.
+    pub fn save(&self, path: &Path) -> Result<(), HighScoreError> {
+        let io_error = |error| HighScoreError::Io {
+            path: path.to_path_buf(),
+            error,
+        };
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(io_error)?;
+        }
+        let contents =
+            serde_json::to_string_pretty(self).map_err(|error| HighScoreError::Serialize {
+                path: path.to_path_buf(),
+                error,
+            })?;
+        fs::write(path, contents).map_err(io_error)
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    pub fn best(&self) -> Option<&HighScore> {
+        self.entries.first()
+    }
+
+    /// Adds a score to the table, keeping only the best `size` of them.
+    /// Returns the 1 based rank the score got, or None if it didn't make the table.
+    /// Ties go to the score that was there first.
+    pub fn insert(&mut self, high_score: HighScore, size: usize) -> Option<usize> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.score < high_score.score)
+            .unwrap_or_else(|| self.entries.len());
+        if index >= size {
+            return None;
+        }
+        self.entries.insert(index, high_score);
+        self.entries.truncate(size);
+        Some(index + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn high_score(score: u64) -> HighScore {
+        HighScore {
+            score,
+            wave: 1,
+            timestamp: score,
+        }
+    }
+
+    fn scores(high_scores: &HighScores) -> Vec<u64> {
+        high_scores
+            .entries()
+            .iter()
+            .map(|entry| entry.score)
+            .collect()
+    }
+
+    #[test]
+    fn scores_are_kept_highest_first() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.insert(high_score(200), 3), Some(1));
+        assert_eq!(high_scores.insert(high_score(100), 3), Some(2));
+        assert_eq!(high_scores.insert(high_score(300), 3), Some(1));
+        assert_eq!(scores(&high_scores), vec![300, 200, 100]);
+        assert_eq!(high_scores.best().map(|best| best.score), Some(300));
+    }
+
+    #[test]
+    fn the_table_is_cut_down_to_its_size() {
+        let mut high_scores = HighScores::default();
+        for score in &[100, 200, 300] {
+            high_scores.insert(high_score(*score), 2);
+        }
+        assert_eq!(scores(&high_scores), vec![300, 200]);
+        assert_eq!(high_scores.insert(high_score(50), 2), None);
+        assert_eq!(scores(&high_scores), vec![300, 200]);
+    }
+
+    #[test]
+    fn ties_go_to_the_score_that_was_there_first() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(high_score(100), 2);
+        let first = high_scores.entries()[0].timestamp;
+        let tie = HighScore {
+            score: 100,
+            wave: 2,
+            timestamp: first + 1,
+        };
+        assert_eq!(high_scores.insert(tie, 2), Some(2));
+        assert_eq!(high_scores.entries()[0].timestamp, first);
+    }
+
+    #[test]
+    fn a_table_with_no_room_takes_nothing() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.insert(high_score(100), 0), None);
+        assert!(high_scores.entries().is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::config::settings::Score as ScoreSettings;
+use crate::game::enemy::EnemyType;
+use std::collections::HashMap;
+
+pub mod high_scores;
+
+/// Score keeps track of the points earned during a single game.
+/// Every hit on an enemy builds up a combo as long as hits keep landing within the combo window,
+/// and kills are worth more the longer the combo is.
+pub struct Score {
+    points: u64,
+    combo: u32,
+    // Seconds since the last hit, the combo is dropped once this passes the combo window
+    time_since_hit: f64,
+    kill_points: HashMap<EnemyType, u32>,
+    combo_window: f64,
+    combo_step: f64,
+    max_multiplier: f64,
+}
+
+impl Score {
+    pub fn new(config: &ScoreSettings) -> Score {
+        Score {
+            points: 0,
+            combo: 0,
+            time_since_hit: 0.0,
+            kill_points: config.kill_points.clone(),
+            combo_window: config.combo_window,
+            combo_step: config.combo_step,
+            max_multiplier: config.max_multiplier,
+        }
+    }
+
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+
+    /// How many hits in a row have landed within the combo window.
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    /// What kill points are currently multiplied by.
+    pub fn multiplier(&self) -> f64 {
+        let bonus = f64::from(self.combo.saturating_sub(1)) * self.combo_step;
+        (1.0 + bonus).min(self.max_multiplier)
+    }
+
+    /// Moves the combo timer forward by `dt` seconds, dropping the combo if it runs out.
+    pub fn update(&mut self, dt: f64) {
+        self.time_since_hit += dt;
+        if self.time_since_hit > self.combo_window {
+            self.combo = 0;
+        }
+    }
+
+    /// Extends the combo. Called for every hit on an enemy, whether it kills them or not.
+    pub fn hit(&mut self) {
+        self.combo += 1;
+        self.time_since_hit = 0.0;
+    }
+
+    /// Awards the points for killing an enemy and returns how many were awarded.
+    pub fn kill(&mut self, enemy: EnemyType) -> u64 {
+        let base = match self.kill_points.get(&enemy) {
+            Some(points) => f64::from(*points),
+            None => {
+                println!("No kill points configured for {:?}", enemy);
+                0.0
+            }
+        };
+        let awarded = (base * self.multiplier()).round() as u64;
+        self.points += awarded;
+        awarded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Babies are worth 10 points, each hit in a combo adds half to the multiplier up to 2x
+    fn score() -> Score {
+        let mut kill_points = HashMap::new();
+        kill_points.insert(EnemyType::Baby, 10);
+        Score::new(&ScoreSettings {
+            kill_points,
+            combo_window: 2.0,
+            combo_step: 0.5,
+            max_multiplier: 2.0,
+            data_dir: String::new(),
+            high_score_count: 10,
+        })
+    }
+
+    fn assert_multiplier(score: &Score, expected: f64) {
+        assert!((score.multiplier() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn each_hit_in_a_combo_steps_up_the_multiplier() {
+        let mut score = score();
+        assert_multiplier(&score, 1.0);
+        score.hit();
+        assert_multiplier(&score, 1.0);
+        score.hit();
+        assert_multiplier(&score, 1.5);
+        assert_eq!(score.kill(EnemyType::Baby), 15);
+        score.hit();
+        assert_multiplier(&score, 2.0);
+    }
+
+    #[test]
+    fn the_multiplier_stops_at_its_max() {
+        let mut score = score();
+        for _ in 0..10 {
+            score.hit();
+        }
+        assert_eq!(score.combo(), 10);
+        assert_multiplier(&score, 2.0);
+        assert_eq!(score.kill(EnemyType::Baby), 20);
+    }
+
+    #[test]
+    fn the_combo_decays_once_the_window_passes() {
+        let mut score = score();
+        score.hit();
+        score.hit();
+        score.update(1.5);
+        assert_eq!(score.combo(), 2);
+        // Landing a hit restarts the window
+        score.hit();
+        score.update(1.5);
+        assert_eq!(score.combo(), 3);
+        score.update(1.0);
+        assert_eq!(score.combo(), 0);
+        assert_multiplier(&score, 1.0);
+    }
+
+    #[test]
+    fn kills_add_up() {
+        let mut score = score();
+        score.kill(EnemyType::Baby);
+        score.hit();
+        score.hit();
+        score.kill(EnemyType::Baby);
+        assert_eq!(score.points(), 25);
+        // Enemies with no configured points are worth nothing
+        assert_eq!(score.kill(EnemyType::Boss), 0);
+        assert_eq!(score.points(), 25);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::config::settings::Settings;
+use crate::game::animation::hitbox::Hitboxes;
+use crate::game::assets::{AssetError, Assets};
+use crate::game::audio::{Audio, AudioBackend, NullBackend};
+use crate::game::hud::Hud;
+use crate::game::level::{Level, LevelError};
+use crate::game::score::high_scores::{HighScore, HighScores};
+use crate::game::text;
+use crate::game::waves::WaveError;
+use crate::game::world::World;
+use graphics::{clear, rectangle};
+use opengl_graphics::{GlGraphics, GlyphCache};
+use piston_window::{Button, ButtonArgs, ButtonState, Context, Key, Motion};
+use std::error::Error;
+use std::fmt;
+
+const FONT: &str = "fonts/DejaVuSans.ttf";
+const TITLE_SIZE: u32 = 64;
+const MESSAGE_SIZE: u32 = 24;
+const TEXT_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
+const OVERLAY_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 0.7];
+
+#[derive(Debug)]
+pub enum GameError {
+    Level(LevelError),
+    /// The level loaded but the waves in the config can't be spawned in it.
+    Waves(WaveError),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::Level(error) => write!(f, "{}", error),
+            GameError::Waves(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for GameError {}
+
+impl From<LevelError> for GameError {
+    fn from(error: LevelError) -> GameError {
+        GameError::Level(error)
+    }
+}
+
+impl From<WaveError> for GameError {
+    fn from(error: WaveError) -> GameError {
+        GameError::Waves(error)
+    }
+}
+
+/// The screen the game is currently on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameState {
+    MainMenu,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+/// Game is the top level of the game. It owns the World and decides which state the game is in,
+/// routing input and rendering to whatever the current state needs.
+pub struct Game {
+    config: Settings,
+    // Loaded once and shared by every world, so restarting doesn't read the map again
+    level: Level,
+    // Read from the sprite sheets once for the same reason
+    hitboxes: Hitboxes,
+    state: GameState,
+    world: World,
+    hud: Hud,
+    // Plays nothing until a real backend is set, main sets one up once there is a window
+    audio: Audio,
+    // Only present once graphics have been loaded
+    glyphs: Option<GlyphCache<'static>>,
+    should_quit: bool,
+    // Best score in the high score table, shown on the menus
+    best_score: Option<u64>,
+    // Where the last game ended up in the high score table
+    high_score_rank: Option<usize>,
+}
+
+impl Game {
+    pub fn new(config: Settings) -> Result<Game, GameError> {
+        let level = Level::load(&config.level.map)?;
+        // Everything has a hitbox to fall back on, so the game can still be played without the ones drawn on the art
+        let hitboxes = Hitboxes::load().unwrap_or_else(|error| {
+            println!("Could not load hitboxes: {}", error);
+            Hitboxes::default()
+        });
+        let world = World::new(&config, &level, &hitboxes)?;
+        let best_score = match HighScores::load(&HighScores::path(&config.score.data_dir)) {
+            Ok(high_scores) => high_scores.best().map(|high_score| high_score.score),
+            Err(error) => {
+                println!("Could not load high scores: {}", error);
+                None
+            }
+        };
+        let mut audio = Audio::new(&config.audio, Box::new(NullBackend));
+        audio.play_music_for(GameState::MainMenu);
+        Ok(Game {
+            config,
+            level,
+            hitboxes,
+            state: GameState::MainMenu,
+            world,
+            hud: Hud::new(),
+            audio,
+            glyphs: None,
+            should_quit: false,
+            best_score,
+            high_score_rank: None,
+        })
+    }
+
+    /// Loads the sprites and fonts. Like World::load_sprites this needs the View's OpenGL context.
+    pub fn load_graphics(&mut self) -> Result<(), AssetError> {
+        self.glyphs = Some(Assets::new()?.glyphs(FONT)?);
+        self.world.load_sprites()
+    }
+
+    /// Plays sound and music through `backend` from now on.
+    pub fn set_audio_backend(&mut self, backend: Box<dyn AudioBackend>) {
+        self.audio.set_backend(backend);
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    /// Moves to another screen, switching to its music.
+    fn enter(&mut self, state: GameState) {
+        self.state = state;
+        self.audio.play_music_for(state);
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    /// Throws away the current world and builds a new one from the config.
+    fn restart(&mut self) {
+        self.world = match World::new(&self.config, &self.level, &self.hitboxes) {
+            Ok(world) => world,
+            Err(error) => {
+                println!("Could not restart: {}", error);
+                return;
+            }
+        };
+        self.hud = Hud::new();
+        if self.glyphs.is_some() {
+            if let Err(error) = self.world.load_sprites() {
+                println!("Could not load sprites: {}", error);
+            }
+        }
+        self.enter(GameState::Playing);
+    }
+
+    fn pause(&mut self) {
+        // Keys that are released while paused would otherwise stay held down
+        self.world.clear_input();
+        self.enter(GameState::Paused);
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if let GameState::Playing = self.state {
+            self.world.advance(dt);
+            for message in self.world.take_messages() {
+                self.hud.show(message);
+            }
+            for sound in self.world.take_sounds() {
+                self.audio.play(sound);
+            }
+            for error in self.world.take_errors() {
+                println!("{}", error);
+            }
+            self.hud.update(dt);
+            if self.world.is_game_over() {
+                self.world.clear_input();
+                self.record_high_score();
+                self.enter(GameState::GameOver);
+            }
+        }
+    }
+
+    /// Adds the game that just ended to the high score table.
+    /// The table is read fresh so that a file that can't be read is never overwritten.
+    fn record_high_score(&mut self) {
+        let path = HighScores::path(&self.config.score.data_dir);
+        let mut high_scores = match HighScores::load(&path) {
+            Ok(high_scores) => high_scores,
+            Err(error) => {
+                println!("Could not load high scores: {}", error);
+                self.high_score_rank = None;
+                return;
+            }
+        };
+        let high_score = HighScore::now(self.world.score().points(), self.world.current_wave());
+        self.high_score_rank = high_scores.insert(high_score, self.config.score.high_score_count);
+        if self.high_score_rank.is_some() {
+            if let Err(error) = high_scores.save(&path) {
+                println!("Could not save high scores: {}", error);
+            }
+        }
+        self.best_score = high_scores.best().map(|high_score| high_score.score);
+    }
+
+    pub fn handle_mouse(&mut self, motion: Motion) {
+        if let GameState::Playing = self.state {
+            self.world.handle_mouse(motion);
+        }
+    }
+
+    pub fn handle_button_event(&mut self, button: ButtonArgs) {
+        let pressed_key = match (button.state, button.button) {
+            (ButtonState::Press, Button::Keyboard(key)) => Some(key),
+            _ => None,
+        };
+        match self.state {
+            GameState::MainMenu => match pressed_key {
+                Some(Key::Return) | Some(Key::Space) => self.restart(),
+                Some(Key::Escape) => self.should_quit = true,
+                _ => {}
+            },
+            GameState::Playing => match pressed_key {
+                Some(Key::Escape) | Some(Key::P) => self.pause(),
+                _ => self.world.handle_button_event(button),
+            },
+            GameState::Paused => match pressed_key {
+                Some(Key::Escape) | Some(Key::P) => self.enter(GameState::Playing),
+                Some(Key::R) => self.restart(),
+                Some(Key::Q) => self.enter(GameState::MainMenu),
+                _ => {}
+            },
+            GameState::GameOver => match pressed_key {
+                Some(Key::Return) | Some(Key::R) => self.restart(),
+                Some(Key::Escape) => self.enter(GameState::MainMenu),
+                _ => {}
+            },
+        }
+    }
+
+    pub fn render(&mut self, context: Context, graphics: &mut GlGraphics) {
+        match self.state {
+            GameState::MainMenu => {
+                clear([0.8, 0.8, 0.8, 1.0], graphics);
+                let best = match self.best_score {
+                    Some(score) => format!("Best score {}", score),
+                    None => String::from("No high score yet"),
+                };
+                self.draw_lines(
+                    &[
+                        "Vaxxinate",
+                        best.as_str(),
+                        "Press Enter to start",
+                        "Esc to quit",
+                    ],
+                    context,
+                    graphics,
+                );
+            }
+            GameState::Playing => {
+                self.render_world(context, graphics);
+            }
+            GameState::Paused => {
+                self.render_world(context, graphics);
+                Game::draw_overlay(context, graphics);
+                self.draw_lines(
+                    &[
+                        "Paused",
+                        "Esc to resume",
+                        "R to restart",
+                        "Q for the main menu",
+                    ],
+                    context,
+                    graphics,
+                );
+            }
+            GameState::GameOver => {
+                self.render_world(context, graphics);
+                Game::draw_overlay(context, graphics);
+                let score = format!(
+                    "Scored {} and made it to wave {}",
+                    self.world.score().points(),
+                    self.world.current_wave()
+                );
+                let rank = match (self.high_score_rank, self.best_score) {
+                    (Some(rank), _) => format!("New high score, rank {}", rank),
+                    (None, Some(best)) => format!("Best score {}", best),
+                    (None, None) => String::new(),
+                };
+                self.draw_lines(
+                    &[
+                        "Game over",
+                        score.as_str(),
+                        rank.as_str(),
+                        "Enter to play again",
+                        "Esc for the main menu",
+                    ],
+                    context,
+                    graphics,
+                );
+            }
+        }
+    }
+
+    /// Draws the world with the HUD over it.
+    fn render_world(&mut self, context: Context, graphics: &mut GlGraphics) {
+        self.world.render(context, context.transform, graphics);
+        if let Some(glyphs) = self.glyphs.as_mut() {
+            self.hud.render(&self.world, glyphs, context, graphics);
+        }
+    }
+
+    fn draw_overlay(context: Context, graphics: &mut GlGraphics) {
+        let [width, height] = context.get_view_size();
+        rectangle(
+            OVERLAY_COLOR,
+            [0.0, 0.0, width, height],
+            context.transform,
+            graphics,
+        );
+    }
+
+    /// Draws a title followed by smaller lines of text, centered in the view.
+    fn draw_lines(&mut self, lines: &[&str], context: Context, graphics: &mut GlGraphics) {
+        let glyphs = match self.glyphs.as_mut() {
+            Some(glyphs) => glyphs,
+            None => return,
+        };
+        let [_, height] = context.get_view_size();
+        let mut y = height / 3.0;
+        for (index, line) in lines.iter().enumerate() {
+            let size = if index == 0 { TITLE_SIZE } else { MESSAGE_SIZE };
+            text::draw_centered(glyphs, line, size, TEXT_COLOR, y, &context, graphics);
+            y += f64::from(size) * 1.5;
+        }
+    }
+}
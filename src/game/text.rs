@@ -0,0 +1,72 @@
+use graphics::character::CharacterCache;
+use graphics::text::Text;
+use graphics::types::{Color, FontSize};
+use graphics::{Context, Transformed};
+use opengl_graphics::{GlGraphics, GlyphCache};
+use std::sync::Once;
+
+// Text is drawn every frame, so a glyph that can't be drawn is only reported the first time
+static REPORT_DRAW_ERROR: Once = Once::new();
+
+/// Draws a line of text with its baseline at `y`, centered horizontally in the view.
+pub fn draw_centered(
+    glyphs: &mut GlyphCache,
+    text: &str,
+    size: FontSize,
+    color: Color,
+    y: f64,
+    context: &Context,
+    graphics: &mut GlGraphics,
+) {
+    let width = glyphs.width(size, text).unwrap_or(0.0);
+    let [view_width, _] = context.get_view_size();
+    draw(
+        glyphs,
+        text,
+        size,
+        color,
+        [(view_width - width) / 2.0, y],
+        context,
+        graphics,
+    );
+}
+
+/// Draws a line of text with its baseline at `position[1]`, ending at `position[0]`.
+pub fn draw_right_aligned(
+    glyphs: &mut GlyphCache,
+    text: &str,
+    size: FontSize,
+    color: Color,
+    position: [f64; 2],
+    context: &Context,
+    graphics: &mut GlGraphics,
+) {
+    let width = glyphs.width(size, text).unwrap_or(0.0);
+    draw(
+        glyphs,
+        text,
+        size,
+        color,
+        [position[0] - width, position[1]],
+        context,
+        graphics,
+    );
+}
+
+/// Draws a line of text with its baseline starting at `position`.
+pub fn draw(
+    glyphs: &mut GlyphCache,
+    text: &str,
+    size: FontSize,
+    color: Color,
+    position: [f64; 2],
+    context: &Context,
+    graphics: &mut GlGraphics,
+) {
+    let transform = context.transform.trans(position[0], position[1]);
+    if let Err(error) =
+        Text::new_color(color, size).draw(text, glyphs, &context.draw_state, transform, graphics)
+    {
+        REPORT_DRAW_ERROR.call_once(|| println!("Could not draw text: {:?}", error));
+    }
+}
@@ -11,7 +11,6 @@ impl View {
     pub fn new(config: &Settings) -> View {
         let open_gl = OpenGL::V3_2;
         let window = WindowSettings::new("Zombies", [config.window.width, config.window.height])
-            .exit_on_esc(true)
             .graphics_api(open_gl)
             .build()
             .unwrap();
@@ -0,0 +1,386 @@
+use crate::config::settings::{Wave, Waves};
+use crate::game::enemy::EnemyType;
+use crate::game::level::SpawnZone;
+use nalgebra::Vector2;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+// How many random points are tried before giving up on a spawn until the next update
+const SPAWN_ATTEMPTS: u32 = 10;
+
+#[derive(Debug)]
+pub enum WaveError {
+    /// The wave with this (1 based) number has no spawn zone in the level that enemies can be spawned in.
+    NoSpawnZones(usize),
+}
+
+impl fmt::Display for WaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WaveError::NoSpawnZones(wave) => {
+                write!(f, "wave {} has no usable spawn zone in the level", wave)
+            }
+        }
+    }
+}
+
+impl Error for WaveError {}
+
+/// Things the WaveDirector wants the World to react to.
+pub enum WaveEvent {
+    Spawn {
+        enemy: EnemyType,
+        position: Vector2<f64>,
+    },
+    /// The wave with this (1 based) number has been spawned and every enemy in it is gone.
+    WaveCleared(usize),
+    AllWavesCleared,
+}
+
+enum DirectorState {
+    Spawning,
+    WaitingForClear,
+    Intermission(f64),
+    Finished,
+}
+
+/// WaveDirector decides when and where enemies are spawned based on the waves in the config.
+/// It does not insert anything itself, it hands back WaveEvents for the World to act on.
+pub struct WaveDirector {
+    waves: Vec<Wave>,
+    // The spawn zones for each wave, looked up from the level
+    spawn_zones: Vec<Vec<SpawnZone>>,
+    intermission: f64,
+    current_wave: usize,
+    spawned_in_wave: u32,
+    time_until_spawn: f64,
+    state: DirectorState,
+}
+
+impl WaveDirector {
+    /// `level_zones` are the level's spawn zones by name, the waves in the config pick from these.
+    /// Zones without an area are skipped, and every wave has to be left with at least one zone.
+    pub fn new(
+        config: &Waves,
+        level_zones: &HashMap<String, SpawnZone>,
+    ) -> Result<WaveDirector, WaveError> {
+        for (name, zone) in level_zones {
+            if !zone.has_area() {
+                println!(
+                    "Spawn zone {} has no area, nothing will be spawned in it",
+                    name
+                );
+            }
+        }
+        let spawn_zones: Vec<Vec<SpawnZone>> = config
+            .list
+            .iter()
+            .map(|wave| WaveDirector::wave_zones(wave, level_zones))
+            .collect();
+        if let Some(wave) = spawn_zones.iter().position(Vec::is_empty) {
+            return Err(WaveError::NoSpawnZones(wave + 1));
+        }
+        let state = if config.list.is_empty() {
+            DirectorState::Finished
+        } else {
+            DirectorState::Spawning
+        };
+        Ok(WaveDirector {
+            waves: config.list.clone(),
+            spawn_zones,
+            intermission: config.intermission,
+            current_wave: 0,
+            spawned_in_wave: 0,
+            time_until_spawn: 0.0,
+            state,
+        })
+    }
+
+    /// The zones a wave can spawn in, leaving out the ones with no area to pick a point from.
+    fn wave_zones(wave: &Wave, level_zones: &HashMap<String, SpawnZone>) -> Vec<SpawnZone> {
+        let zones: Vec<&SpawnZone> = if wave.spawn_zones.is_empty() {
+            level_zones.values().collect()
+        } else {
+            wave.spawn_zones
+                .iter()
+                .filter_map(|name| {
+                    let zone = level_zones.get(name);
+                    if zone.is_none() {
+                        println!("The level has no spawn zone called {}", name);
+                    }
+                    zone
+                })
+                .collect()
+        };
+        zones
+            .into_iter()
+            .filter(|zone| zone.has_area())
+            .cloned()
+            .collect()
+    }
+
+    /// The type of the `index`th enemy to be spawned in a wave, or None once the whole wave has been spawned.
+    /// A wave's boss comes after every other enemy in it.
+    fn enemy_at(wave: &Wave, index: u32) -> Option<EnemyType> {
+        let mut first_in_group = 0;
+        for group in &wave.enemies {
+            if index < first_in_group + group.count {
+                return Some(group.enemy);
+            }
+            first_in_group += group.count;
+        }
+        if wave.boss && index == first_in_group {
+            Some(EnemyType::Boss)
+        } else {
+            None
+        }
+    }
+
+    /// The 1 based number of the wave that is currently being played.
+    pub fn current_wave(&self) -> usize {
+        self.current_wave + 1
+    }
+
+    /// Moves the director forward by `dt` seconds.
+    /// `enemies_alive` is how many enemies are currently in the world, a wave is only cleared once this hits zero.
+    pub fn update(
+        &mut self,
+        dt: f64,
+        player_position: Vector2<f64>,
+        enemies_alive: usize,
+    ) -> Vec<WaveEvent> {
+        let mut events = vec![];
+        match self.state {
+            DirectorState::Spawning => {
+                let wave = &self.waves[self.current_wave];
+                let spawn_zones = &self.spawn_zones[self.current_wave];
+                self.time_until_spawn -= dt;
+                match WaveDirector::enemy_at(wave, self.spawned_in_wave) {
+                    Some(enemy) if self.time_until_spawn <= 0.0 => {
+                        if let Some(position) =
+                            WaveDirector::spawn_position(wave, spawn_zones, player_position)
+                        {
+                            events.push(WaveEvent::Spawn { enemy, position });
+                            self.spawned_in_wave += 1;
+                            self.time_until_spawn = wave.spawn_interval;
+                        }
+                    }
+                    Some(_) => {}
+                    None => self.state = DirectorState::WaitingForClear,
+                }
+            }
+            DirectorState::WaitingForClear => {
+                if enemies_alive == 0 {
+                    events.push(WaveEvent::WaveCleared(self.current_wave()));
+                    if self.current_wave + 1 < self.waves.len() {
+                        self.state = DirectorState::Intermission(self.intermission);
+                    } else {
+                        events.push(WaveEvent::AllWavesCleared);
+                        self.state = DirectorState::Finished;
+                    }
+                }
+            }
+            DirectorState::Intermission(remaining) => {
+                let remaining = remaining - dt;
+                if remaining <= 0.0 {
+                    self.current_wave += 1;
+                    self.spawned_in_wave = 0;
+                    self.time_until_spawn = 0.0;
+                    self.state = DirectorState::Spawning;
+                } else {
+                    self.state = DirectorState::Intermission(remaining);
+                }
+            }
+            DirectorState::Finished => {}
+        }
+        events
+    }
+
+    /// Picks a random point in one of the wave's spawn zones that is far enough away from the player.
+    fn spawn_position(
+        wave: &Wave,
+        spawn_zones: &[SpawnZone],
+        player_position: Vector2<f64>,
+    ) -> Option<Vector2<f64>> {
+        let mut rng = rand::thread_rng();
+        for _ in 0..SPAWN_ATTEMPTS {
+            let zone: &SpawnZone = spawn_zones.choose(&mut rng)?;
+            let position = Vector2::new(
+                rng.gen_range(zone.x, zone.x + zone.width),
+                rng.gen_range(zone.y, zone.y + zone.height),
+            );
+            if (position - player_position).norm() >= wave.min_player_distance {
+                return Some(position);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::WaveEnemies;
+
+    fn wave(enemies: &[(EnemyType, u32)], boss: bool) -> Wave {
+        Wave {
+            enemies: enemies
+                .iter()
+                .map(|&(enemy, count)| WaveEnemies { enemy, count })
+                .collect(),
+            boss,
+            spawn_interval: 1.0,
+            spawn_zones: vec![],
+            min_player_distance: 0.0,
+        }
+    }
+
+    fn zones(zones: &[(&str, SpawnZone)]) -> HashMap<String, SpawnZone> {
+        zones
+            .iter()
+            .map(|(name, zone)| (name.to_string(), zone.clone()))
+            .collect()
+    }
+
+    fn zone(width: f64, height: f64) -> SpawnZone {
+        SpawnZone {
+            x: 0.0,
+            y: 0.0,
+            width,
+            height,
+        }
+    }
+
+    fn director(list: Vec<Wave>) -> WaveDirector {
+        let config = Waves {
+            intermission: 2.0,
+            list,
+        };
+        WaveDirector::new(&config, &zones(&[("yard", zone(10.0, 10.0))])).unwrap()
+    }
+
+    fn update(director: &mut WaveDirector, dt: f64, enemies_alive: usize) -> Vec<WaveEvent> {
+        director.update(dt, Vector2::new(1000.0, 1000.0), enemies_alive)
+    }
+
+    #[test]
+    fn enemies_are_spawned_group_by_group_with_the_boss_last() {
+        let wave = wave(&[(EnemyType::Baby, 2), (EnemyType::Crawler, 1)], true);
+        let order: Vec<Option<EnemyType>> = (0..5)
+            .map(|index| WaveDirector::enemy_at(&wave, index))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                Some(EnemyType::Baby),
+                Some(EnemyType::Baby),
+                Some(EnemyType::Crawler),
+                Some(EnemyType::Boss),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn waves_without_a_boss_end_after_their_last_group() {
+        let wave = wave(&[(EnemyType::Toddler, 1)], false);
+        assert_eq!(WaveDirector::enemy_at(&wave, 1), None);
+    }
+
+    #[test]
+    fn spawns_are_spaced_out_by_the_spawn_interval() {
+        let mut director = director(vec![wave(&[(EnemyType::Baby, 2)], false)]);
+        let first = update(&mut director, 0.1, 0);
+        assert!(matches!(
+            first.as_slice(),
+            [WaveEvent::Spawn {
+                enemy: EnemyType::Baby,
+                ..
+            }]
+        ));
+        assert!(update(&mut director, 0.5, 1).is_empty());
+        assert!(matches!(
+            update(&mut director, 0.5, 1).as_slice(),
+            [WaveEvent::Spawn { .. }]
+        ));
+    }
+
+    #[test]
+    fn enemies_are_spawned_inside_the_spawn_zones() {
+        let mut director = director(vec![wave(&[(EnemyType::Baby, 20)], false)]);
+        for _ in 0..20 {
+            for event in update(&mut director, 1.0, 0) {
+                if let WaveEvent::Spawn { position, .. } = event {
+                    assert!(position.x >= 0.0 && position.x <= 10.0);
+                    assert!(position.y >= 0.0 && position.y <= 10.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn waves_are_cleared_once_every_enemy_is_gone_and_then_the_next_one_starts() {
+        let mut director = director(vec![
+            wave(&[(EnemyType::Baby, 1)], false),
+            wave(&[(EnemyType::Crawler, 1)], false),
+        ]);
+        update(&mut director, 0.1, 0);
+        // Notices the whole wave has been spawned
+        assert!(update(&mut director, 0.1, 1).is_empty());
+        assert!(update(&mut director, 0.1, 1).is_empty());
+        assert!(matches!(
+            update(&mut director, 0.1, 0).as_slice(),
+            [WaveEvent::WaveCleared(1)]
+        ));
+
+        assert!(update(&mut director, 1.0, 0).is_empty());
+        assert_eq!(director.current_wave(), 1);
+        update(&mut director, 1.0, 0);
+        assert_eq!(director.current_wave(), 2);
+
+        assert!(matches!(
+            update(&mut director, 0.1, 0).as_slice(),
+            [WaveEvent::Spawn {
+                enemy: EnemyType::Crawler,
+                ..
+            }]
+        ));
+        update(&mut director, 0.1, 1);
+        assert!(matches!(
+            update(&mut director, 0.1, 0).as_slice(),
+            [WaveEvent::WaveCleared(2), WaveEvent::AllWavesCleared]
+        ));
+        assert!(update(&mut director, 0.1, 0).is_empty());
+    }
+
+    #[test]
+    fn waves_need_a_zone_with_an_area() {
+        let config = Waves {
+            intermission: 2.0,
+            list: vec![wave(&[(EnemyType::Baby, 1)], false)],
+        };
+        let level_zones = zones(&[("point", zone(0.0, 0.0)), ("line", zone(10.0, 0.0))]);
+        assert!(matches!(
+            WaveDirector::new(&config, &level_zones),
+            Err(WaveError::NoSpawnZones(1))
+        ));
+    }
+
+    #[test]
+    fn waves_need_the_zones_they_name_to_exist() {
+        let mut named = wave(&[(EnemyType::Baby, 1)], false);
+        named.spawn_zones = vec!["porch".to_string()];
+        let config = Waves {
+            intermission: 2.0,
+            list: vec![wave(&[(EnemyType::Baby, 1)], false), named],
+        };
+        let level_zones = zones(&[("yard", zone(10.0, 10.0))]);
+        assert!(matches!(
+            WaveDirector::new(&config, &level_zones),
+            Err(WaveError::NoSpawnZones(2))
+        ));
+    }
+}
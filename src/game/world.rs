@@ -1,89 +1,347 @@
-use crate::config::settings::Settings;
+use crate::config::settings::{Pickups, Projectiles, Settings};
+use crate::game::animation::hitbox::Hitboxes;
+use crate::game::animation::{AnimationState, Animator};
+use crate::game::assets::{AssetError, Assets};
+use crate::game::audio::SoundEffect;
 use crate::game::bullet::{BulletUserData, InsertedBullet};
-use crate::game::enemy::baby::{BabyUserData, InsertedBaby};
-use crate::game::insertable::{Insertable, Inserted, InsertedBody};
+use crate::game::camera::Camera;
+use crate::game::collision::layers::Layer;
+use crate::game::collision::{CollisionHandlers, ContactPhase};
+use crate::game::enemy::baby::Baby;
+use crate::game::enemy::boss::Boss;
+use crate::game::enemy::crawler::Crawler;
+use crate::game::enemy::parent::Parent;
+use crate::game::enemy::toddler::Toddler;
+use crate::game::enemy::{Enemy, EnemyType, Surroundings};
+use crate::game::entity::{EntityId, EntityKind, EntityRegistry};
+use crate::game::health::{Damage, DamageKind, DeathEvent, Health};
+use crate::game::insertable::{Insertable, Inserted, InsertedBody, PhysicsInsertable};
+use crate::game::level::tiles::TileMap;
+use crate::game::level::Level;
+use crate::game::navigation::grid::Bounds;
+use crate::game::navigation::Navigation;
+use crate::game::particles::{ParticleEvent, ParticleSystem};
 use crate::game::physics_world::PhysicsWorld;
+use crate::game::pickup::effects::TimedEffect;
+use crate::game::pickup::{InsertedPickup, PickupKind, PickupUserData};
 use crate::game::player::character::Character;
+use crate::game::player::weapon::Weapon;
+use crate::game::score::Score;
+use crate::game::waves::{WaveDirector, WaveError, WaveEvent};
 use input::MouseButton;
 use nalgebra::Vector2;
 use ncollide2d::narrow_phase::ContactEvent;
-use nphysics2d::object::{BodyPartHandle, DefaultBodySet, DefaultColliderSet};
+use ncollide2d::query::Proximity;
+use nphysics2d::object::{BodyStatus, ColliderDesc, DefaultBodyHandle, RigidBodyDesc};
 use opengl_graphics::GlGraphics;
 use opengl_graphics::Texture;
 use piston_window::math::Matrix2d;
 use piston_window::{clear, Button, ButtonArgs, ButtonState, Context, Graphics, Key, Motion};
+use rand::seq::SliceRandom;
+use rand::Rng;
 use sprite::{Scene, Sprite};
 use std::collections::{HashMap, HashSet};
-use std::rc::Rc;
+use std::error::Error;
+use std::fmt;
 use uuid::Uuid;
 
+// How far from where an enemy died the enemies it leaves behind are spawned
+const LEFT_BEHIND_SPREAD: f64 = 30.0;
+// How far from an enemy the enemies it summons are spawned, far enough to land clear of the boss's body
+const SUMMON_SPREAD: f64 = 200.0;
+
+/// Problems that come up while the world is running. None of them stop the game, they are picked up with `take_errors` to be reported.
+#[derive(Debug)]
+pub enum WorldError {
+    /// Something was inserted but its sprite could not be added, so it won't be drawn.
+    /// Each texture is only reported the first time it fails.
+    Sprite(AssetError),
+    /// There was no walkable spot in the level to spawn an enemy of this type.
+    NowhereToSpawn(EnemyType),
+}
+
+impl fmt::Display for WorldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorldError::Sprite(error) => write!(f, "could not add sprite: {}", error),
+            WorldError::NowhereToSpawn(enemy) => {
+                write!(f, "there is nowhere in the level to spawn a {:?}", enemy)
+            }
+        }
+    }
+}
+
+impl Error for WorldError {}
+
 /// World struct. Contains the physics world, sprite scene, and other things that we need to keep track of and react to during the game loop.
+/// A World is headless until `load_sprites` is called, so it can be built and stepped without an OpenGL context.
 pub struct World {
     physics_world: PhysicsWorld,
     scene: Scene<Texture>,
+    // The level's tile layers, drawn underneath the scene
+    tiles: TileMap,
+    // Only present once sprites have been loaded
+    assets: Option<Assets>,
+    // Textures that could not be added as sprites, they are not tried again
+    failed_textures: HashSet<&'static str>,
+    entities: EntityRegistry,
+    collision_handlers: CollisionHandlers,
     character: Character,
-    babies: HashMap<Uuid, InsertedBaby>,
+    player_id: EntityId,
+    enemies: HashMap<Uuid, Box<dyn Enemy>>,
     bullets: HashMap<Uuid, InsertedBullet>,
+    pickups: HashMap<Uuid, InsertedPickup>,
+    // Sprites of dead enemies playing out their die clip, by sprite uuid. They are removed once it finishes.
+    corpses: Vec<(Uuid, Animator)>,
+    waves: WaveDirector,
+    navigation: Navigation,
+    camera: Camera,
+    // Drawn on top of the scene
+    particles: ParticleSystem,
+    level_bounds: Bounds,
+    projectiles: Projectiles,
+    pickup_config: Pickups,
+    // Collider shapes drawn on the art, used by everything spawned into the world
+    hitboxes: Hitboxes,
+    // Deaths that happened during this update, handled once contact events have all been processed
+    deaths: Vec<DeathEvent>,
+    // Enemies currently touching the player, the player takes contact damage while there are any
+    enemies_touching_player: HashSet<EntityId>,
+    contact_damage: Damage,
+    game_over: bool,
+    score: Score,
+    // Messages for the HUD that have not been picked up yet
+    messages: Vec<String>,
+    // Sound effects for events that happened since they were last picked up
+    sounds: Vec<SoundEffect>,
+    // Problems that have not been picked up yet
+    errors: Vec<WorldError>,
     keys_pressed: HashSet<Key>,
+    mouse_buttons_pressed: HashSet<MouseButton>,
+    // Where the cursor is on the screen, it is turned into world space through the camera when it is used
     mouse_position: [f64; 2],
+    timestep: f64,
+    max_substeps: u32,
+    // Real time that has passed but has not been simulated yet
+    accumulator: f64,
+    // How many fixed steps have been simulated since the World was built
+    steps: u64,
 }
 
 impl World {
-    pub fn new(config: &Settings) -> World {
-        let mut scene: Scene<Texture> = Scene::new();
-        let mut physics_world = PhysicsWorld::new();
-        let (body_set, collider_set) = physics_world.body_collider_sets_mut();
-        let character = Character::new(
-            body_set,
-            collider_set,
-            (config.player.spawn_point.x, config.player.spawn_point.y),
-            &mut scene,
+    /// Fails when one of the waves in the config has nowhere in the level to spawn its enemies.
+    pub fn new(config: &Settings, level: &Level, hitboxes: &Hitboxes) -> Result<World, WaveError> {
+        let waves = WaveDirector::new(&config.waves, level.spawn_zones())?;
+        let mut physics_world = PhysicsWorld::new(config);
+        // Level geometry has to be in place before the navigation grid is built from it
+        let mut entities = EntityRegistry::new();
+        World::insert_level_colliders(&mut physics_world, &mut entities, level);
+        let spawn = level.player_spawn();
+        let character_insertable = Character::generate_insertable((spawn.x, spawn.y), hitboxes);
+        let character_inserted = World::insert_physics(&mut physics_world, character_insertable);
+        let player_id = EntityId::new(EntityKind::Player, Uuid::new_v4());
+        entities.register(player_id, &character_inserted);
+        let character = Character::new(character_inserted, &config.player, &config.weapons);
+
+        let level_bounds = level.bounds();
+        let camera = Camera::new(
+            &config.camera,
+            [config.window.width, config.window.height],
+            level_bounds,
+            character.get_position(physics_world.body_set()),
         );
+        let (body_set, collider_set) = physics_world.body_collider_sets_mut();
+        let navigation = Navigation::new(&config.navigation, level_bounds, body_set, collider_set);
 
-        let (test_baby, test_baby_uuid) = World::insert_baby(&mut scene, body_set, collider_set);
-        let mut babies = HashMap::new();
-        babies.insert(test_baby_uuid, test_baby);
+        let mut collision_handlers = CollisionHandlers::new();
+        collision_handlers.register(
+            EntityKind::Bullet,
+            EntityKind::Enemy,
+            World::bullet_hit_enemy,
+        );
+        collision_handlers.register(EntityKind::Bullet, EntityKind::Wall, World::bullet_hit_wall);
+        collision_handlers.register(
+            EntityKind::Player,
+            EntityKind::Enemy,
+            World::enemy_touched_player,
+        );
+        collision_handlers.register_stopped(
+            EntityKind::Player,
+            EntityKind::Enemy,
+            World::enemy_left_player,
+        );
+        collision_handlers.register_sensed(
+            EntityKind::Player,
+            EntityKind::Pickup,
+            World::player_collected_pickup,
+        );
 
-        World {
+        Ok(World {
             physics_world,
+            entities,
+            collision_handlers,
             character,
+            player_id,
             keys_pressed: HashSet::new(),
+            mouse_buttons_pressed: HashSet::new(),
             mouse_position: [0.0, 0.0],
-            scene,
+            scene: Scene::new(),
+            tiles: level.tiles().clone(),
+            assets: None,
+            failed_textures: HashSet::new(),
             bullets: HashMap::new(),
-            babies,
+            enemies: HashMap::new(),
+            pickups: HashMap::new(),
+            corpses: vec![],
+            waves,
+            navigation,
+            camera,
+            particles: ParticleSystem::new(&config.particles),
+            level_bounds,
+            projectiles: config.projectiles.clone(),
+            pickup_config: config.pickups.clone(),
+            hitboxes: hitboxes.clone(),
+            deaths: vec![],
+            enemies_touching_player: HashSet::new(),
+            contact_damage: Damage::new(config.player.contact_damage, DamageKind::Contact),
+            game_over: false,
+            score: Score::new(&config.score),
+            messages: vec![],
+            sounds: vec![],
+            errors: vec![],
+            timestep: config.physics.timestep,
+            max_substeps: config.physics.max_substeps,
+            accumulator: 0.0,
+            steps: 0,
+        })
+    }
+
+    /// Gives each of the level's colliders its own static body on the static world layer.
+    /// They are registered as walls so bullets can be stopped when they hit one.
+    fn insert_level_colliders(
+        physics_world: &mut PhysicsWorld,
+        entities: &mut EntityRegistry,
+        level: &Level,
+    ) {
+        for collider in level.colliders() {
+            let body = RigidBodyDesc::new()
+                .status(BodyStatus::Static)
+                .position(collider.position)
+                .build();
+            let collider_desc = ColliderDesc::new(collider.shape.clone());
+            let inserted = physics_world.insert(PhysicsInsertable::new(
+                body,
+                Some(collider_desc),
+                Layer::StaticWorld,
+            ));
+            if let Some(collider_handle) = inserted.get_collider_handle() {
+                let wall_id = EntityId::new(EntityKind::Wall, Uuid::new_v4());
+                entities.register_collider(wall_id, collider_handle);
+            }
+        }
+    }
+
+    /// Loads the textures for everything in the world and adds their sprites to the scene.
+    /// This needs the OpenGL context created by the View, but the order the World and View are created in does not matter.
+    /// Anything inserted after this is called will get its sprite as soon as it is inserted.
+    pub fn load_sprites(&mut self) -> Result<(), AssetError> {
+        let mut assets = Assets::new()?;
+        self.tiles.load_textures(&mut assets)?;
+        self.particles.load_textures(&mut assets)?;
+        let scene = &mut self.scene;
+        World::attach_sprite(scene, &mut assets, self.character.inserted_mut())?;
+        for enemy in self.enemies.values_mut() {
+            World::attach_sprite(scene, &mut assets, enemy.body_mut().inserted_mut())?;
+        }
+        for bullet in self.bullets.values_mut() {
+            World::attach_sprite(scene, &mut assets, bullet.inserted_mut())?;
+        }
+        for pickup in self.pickups.values_mut() {
+            World::attach_sprite(scene, &mut assets, pickup.inserted_mut())?;
         }
+        self.assets = Some(assets);
+        Ok(())
     }
 
-    // TODO: This should probably just insert the baby? Or something else should determine when to spawn.
-    fn insert_baby(
+    fn attach_sprite(
         scene: &mut Scene<Texture>,
-        body_set: &mut DefaultBodySet<f64>,
-        collider_set: &mut DefaultColliderSet<f64>,
-    ) -> (InsertedBaby, Uuid) {
-        // Temporary code
-        let (baby_insertable, uuid) = BabyUserData::generate_insertable(Vector2::new(10.0, 10.0));
-
-        let (sprite_tex, rigid_body, collider_desc_option) = baby_insertable.get_parts();
-        let mut baby_sprite = Sprite::from_texture(sprite_tex);
-        baby_sprite.set_position(249.0, 250.0);
-        let baby_id = scene.add_child(baby_sprite);
-
-        let baby_handle = body_set.insert(rigid_body);
-        match collider_desc_option {
-            Some(collider_desc) => {
-                let collider = collider_desc.build(BodyPartHandle(baby_handle, 0));
-                let collider_handle = collider_set.insert(collider);
-                (
-                    InsertedBaby::new(baby_id, baby_handle, Some(collider_handle)),
-                    uuid,
-                )
-            }
-            None => (InsertedBaby::new(baby_id, baby_handle, None), uuid),
+        assets: &mut Assets,
+        inserted: &mut Inserted,
+    ) -> Result<(), AssetError> {
+        if inserted.get_sprite_uuid().is_none() {
+            let sprite = match assets.sprite_sheet(inserted.get_texture_name())? {
+                Some(sprite_sheet) => {
+                    let animator = Animator::new(sprite_sheet);
+                    let texture = assets.texture(animator.sheet().image())?;
+                    let sprite = Sprite::from_texture_rect(texture, animator.src_rect());
+                    inserted.set_animator(animator);
+                    sprite
+                }
+                None => Sprite::from_texture(assets.texture(inserted.get_texture_name())?),
+            };
+            inserted.set_sprite_uuid(scene.add_child(sprite));
+        }
+        Ok(())
+    }
+
+    /// Advances the world by `dt` seconds of real time using fixed size steps.
+    /// Slow frames are caught up on with several steps, up to the configured max_substeps.
+    /// Time that is left over is carried into the next call and is exposed to rendering through `interpolation_alpha`.
+    pub fn advance(&mut self, dt: f64) {
+        if self.game_over {
+            return;
         }
+        self.accumulator += dt;
+        let mut substeps = 0;
+        while self.accumulator >= self.timestep && substeps < self.max_substeps && !self.game_over {
+            self.update();
+            self.step();
+            self.accumulator -= self.timestep;
+            self.steps += 1;
+            substeps += 1;
+        }
+        // If we are still behind after the max substeps drop the extra time
+        //  so that one slow frame can't make every following frame slow too.
+        if self.accumulator >= self.timestep {
+            self.accumulator %= self.timestep;
+        }
+
+        self.extrapolate_sprites();
+        let player_position = self.character.get_position(self.physics_world.body_set());
+        self.camera.follow(player_position, dt);
     }
 
-    /// Steps the physics world forward.
+    /// How many fixed steps have been simulated so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// How far between the last step and the next one the world currently is, from 0.0 to 1.0.
+    pub fn interpolation_alpha(&self) -> f64 {
+        self.accumulator / self.timestep
+    }
+
+    fn extrapolate_sprites(&mut self) {
+        let lookahead = self.interpolation_alpha() * self.timestep;
+        let body_set = self.physics_world.body_set();
+        let scene = &mut self.scene;
+        self.character
+            .inserted()
+            .extrapolate_sprite(body_set, scene, lookahead);
+        for enemy in self.enemies.values() {
+            enemy
+                .body()
+                .inserted()
+                .extrapolate_sprite(body_set, scene, lookahead);
+        }
+        for bullet in self.bullets.values() {
+            bullet
+                .inserted()
+                .extrapolate_sprite(body_set, scene, lookahead);
+        }
+    }
+
+    /// Steps the physics world forward by a single timestep.
     pub fn step(&mut self) {
         self.physics_world.step();
     }
@@ -92,10 +350,13 @@ impl World {
     pub fn update(&mut self) {
         let (body_set, _) = self.physics_world.body_collider_sets_mut();
         let scene = &mut self.scene;
-        self.character.update(body_set, &self.keys_pressed, scene);
+        self.character
+            .update(body_set, &self.keys_pressed, scene, self.timestep);
 
+        // The camera may have moved since the cursor did, so the cursor is turned into world space every update
         self.character
-            .update_rotation(self.mouse_position, body_set);
+            .update_rotation(self.camera.screen_to_world(self.mouse_position), body_set);
+        self.fire_held_weapon();
 
         let _: Vec<_> = self
             .bullets
@@ -106,91 +367,586 @@ impl World {
             .collect();
 
         let _: Vec<_> = self
-            .babies
+            .enemies
             .values()
-            .map(|b| {
-                b.update(body_set, scene);
+            .map(|e| {
+                e.body().update(body_set, scene);
             })
             .collect();
 
+        let _: Vec<_> = self
+            .pickups
+            .values()
+            .map(|p| {
+                p.update(body_set, scene);
+            })
+            .collect();
+
+        self.animate_sprites();
+        self.particles.update(self.timestep);
+        self.expire_bullets();
+        self.expire_pickups();
+        self.update_enemy_ai();
+        self.score.update(self.timestep);
         self.handle_contact_events();
+        self.apply_contact_damage();
+        self.handle_deaths();
+        self.update_waves();
+    }
+
+    /// Keeps an automatic weapon firing while the left mouse button is held, its cooldown limits how often it actually shoots.
+    fn fire_held_weapon(&mut self) {
+        let is_automatic = self
+            .character
+            .weapon()
+            .map_or(false, |weapon| weapon.is_automatic());
+        if is_automatic && self.mouse_buttons_pressed.contains(&MouseButton::Left) {
+            self.fire_weapon();
+        }
+    }
+
+    /// Picks a clip for everything with an animated sprite based on what it is doing, and plays out the corpses.
+    fn animate_sprites(&mut self) {
+        let body_set = self.physics_world.body_set();
+        let scene = &mut self.scene;
+        let dt = self.timestep;
+        let velocity_of = |inserted: &Inserted| {
+            body_set
+                .rigid_body(inserted.get_body_handle())
+                .map_or_else(Vector2::zeros, |rigid_body| rigid_body.velocity().linear)
+        };
+
+        let state = AnimationState::from_velocity(velocity_of(self.character.inserted()));
+        self.character.inserted_mut().animate(scene, state, dt);
+        for enemy in self.enemies.values_mut() {
+            let state = AnimationState::from_velocity(velocity_of(enemy.body().inserted()));
+            enemy.body_mut().inserted_mut().animate(scene, state, dt);
+        }
+        for bullet in self.bullets.values_mut() {
+            bullet
+                .inserted_mut()
+                .animate(scene, AnimationState::Idle, dt);
+        }
+
+        let mut finished = vec![];
+        for (sprite_uuid, animator) in self.corpses.iter_mut() {
+            animator.update(dt);
+            if let Some(sprite) = scene.child_mut(*sprite_uuid) {
+                sprite.set_src_rect(animator.src_rect());
+            }
+            if animator.is_finished() {
+                finished.push(*sprite_uuid);
+            }
+        }
+        self.corpses
+            .retain(|(sprite_uuid, _)| !finished.contains(sprite_uuid));
+        for sprite_uuid in finished {
+            scene.remove_child(sprite_uuid);
+        }
+    }
+
+    /// Despawns every bullet that has run out of lifetime or range, or left the level.
+    fn expire_bullets(&mut self) {
+        let body_set = self.physics_world.body_set();
+        let mut expired = vec![];
+        for (uuid, bullet) in self.bullets.iter_mut() {
+            bullet.advance_age(self.timestep);
+            if bullet.is_expired(body_set, &self.level_bounds) {
+                expired.push(EntityId::new(EntityKind::Bullet, *uuid));
+            }
+        }
+        for id in expired {
+            self.despawn(id);
+        }
+    }
+
+    /// Despawns every pickup that has been left on the ground too long.
+    fn expire_pickups(&mut self) {
+        let mut expired = vec![];
+        for (uuid, pickup) in self.pickups.iter_mut() {
+            pickup.advance_age(self.timestep);
+            if pickup.is_expired() {
+                expired.push(EntityId::new(EntityKind::Pickup, *uuid));
+            }
+        }
+        for id in expired {
+            self.despawn(id);
+        }
+    }
+
+    /// Lets every enemy decide what to do this tick, after working out which of them are inside another's shield.
+    fn update_enemy_ai(&mut self) {
+        let body_set = self.physics_world.body_set_mut();
+        let player_position = self.character.get_position(body_set);
+        let mut enemies = vec![];
+        let mut shields = vec![];
+        for enemy in self.enemies.values() {
+            if let Some(position) = enemy.body().position(body_set) {
+                enemies.push((enemy.enemy_type(), position));
+                if let Some(shield) = enemy.shield() {
+                    shields.push((position, shield));
+                }
+            }
+        }
+        let neighbours: Vec<Vector2<f64>> = enemies.iter().map(|(_, position)| *position).collect();
+        let surroundings = Surroundings {
+            navigation: &self.navigation,
+            player_position,
+            enemies: &enemies,
+            neighbours: &neighbours,
+            dt: self.timestep,
+        };
+        let mut summons = vec![];
+        for enemy in self.enemies.values_mut() {
+            if let Some(position) = enemy.body().position(body_set) {
+                let shield = shields
+                    .iter()
+                    .find(|(shield_position, shield)| {
+                        (shield_position - position).norm() <= shield.radius
+                    })
+                    .map(|(_, shield)| *shield);
+                enemy.set_shield(shield);
+            }
+            enemy.behave(body_set, &surroundings);
+            let summoned = enemy.summons();
+            if !summoned.is_empty() {
+                if let Some(position) = enemy.body().position(body_set) {
+                    summons.push((position, summoned));
+                }
+            }
+        }
+        for (position, summoned) in summons {
+            self.spawn_around(position, summoned, SUMMON_SPREAD);
+        }
+    }
+
+    /// True once the player has died. The world stops advancing at that point.
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn player_health(&self) -> &Health {
+        self.character.health()
+    }
+
+    /// The weapon the player currently has out.
+    pub fn weapon(&self) -> Option<&Weapon> {
+        self.character.weapon()
+    }
+
+    /// Takes every sound effect that should be played for what has happened since this was last called.
+    pub fn take_sounds(&mut self) -> Vec<SoundEffect> {
+        std::mem::take(&mut self.sounds)
+    }
+
+    /// Takes every message for the HUD that has come up since this was last called.
+    pub fn take_messages(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.messages)
+    }
+
+    /// Takes every problem that has come up since this was last called.
+    pub fn take_errors(&mut self) -> Vec<WorldError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// The player's timed pickup effects and how many seconds each has left.
+    pub fn active_effects(&self) -> Vec<(TimedEffect, f64)> {
+        self.character.effects().remaining()
+    }
+
+    /// The boss's health while there is a boss in the world.
+    pub fn boss_health(&self) -> Option<&Health> {
+        self.enemies
+            .values()
+            .find(|enemy| enemy.enemy_type() == EnemyType::Boss)
+            .map(|enemy| enemy.body().health())
+    }
+
+    /// The 1 based number of the wave currently being played.
+    pub fn current_wave(&self) -> usize {
+        self.waves.current_wave()
+    }
+
+    fn update_waves(&mut self) {
+        let player_position = self.character.get_position(self.physics_world.body_set());
+        let wave_events = self
+            .waves
+            .update(self.timestep, player_position, self.enemies.len());
+        for wave_event in wave_events {
+            match wave_event {
+                WaveEvent::Spawn { enemy, position } => self.spawn_enemy(enemy, position),
+                WaveEvent::WaveCleared(wave) => {
+                    self.messages.push(format!("Wave {} cleared", wave))
+                }
+                WaveEvent::AllWavesCleared => self.messages.push(String::from("All waves cleared")),
+            }
+        }
+    }
+
+    fn spawn_enemy(&mut self, enemy: EnemyType, position: Vector2<f64>) {
+        match enemy {
+            EnemyType::Baby => self.insert_enemy::<Baby>(position),
+            EnemyType::Crawler => self.insert_enemy::<Crawler>(position),
+            EnemyType::Toddler => self.insert_enemy::<Toddler>(position),
+            EnemyType::Parent => self.insert_enemy::<Parent>(position),
+            EnemyType::Boss => {
+                self.messages.push(String::from("The boss has arrived"));
+                self.insert_enemy::<Boss>(position)
+            }
+        }
+    }
+
+    /// Spawns `enemies` spread out evenly in a circle of radius `spread` around `center`.
+    /// Places on the circle that are inside level geometry or outside the level are moved to the nearest walkable spot.
+    fn spawn_around(&mut self, center: Vector2<f64>, enemies: Vec<EnemyType>, spread: f64) {
+        let count = enemies.len() as f64;
+        for (index, enemy) in enemies.into_iter().enumerate() {
+            let angle = index as f64 / count * std::f64::consts::PI * 2.0;
+            let offset = Vector2::new(angle.cos(), angle.sin()) * spread;
+            match self.navigation.grid().nearest_walkable(center + offset) {
+                Some(position) => self.spawn_enemy(enemy, position),
+                None => self.errors.push(WorldError::NowhereToSpawn(enemy)),
+            }
+        }
+    }
+
+    fn insert_enemy<E: Enemy + 'static>(&mut self, position: Vector2<f64>) {
+        let inserted = self.insert_insertable(E::spawn(position, &self.hitboxes));
+        let uuid = Uuid::new_v4();
+        self.entities
+            .register(EntityId::new(EntityKind::Enemy, uuid), &inserted);
+        let path = self.navigation.path_follower();
+        self.enemies.insert(uuid, Box::new(E::new(inserted, path)));
     }
 
-    // TODO: Too long to calculate these events in every loop
-    // Actually dealing with these contact events takes a long time.
-    //  Long enough that the game is taking too long to spawn a bullet on click and delays it to the next click.
-    //  May have to do this less frequently?
-    //  During a different game event?
     /// Handles effects that contact events have on the world.
-    /// Used to de-spawn bullets when they collide and other events that occur when two things collide.
+    /// Each collider is looked up in the entity registry and the pair is handed to the collision handler registered for their kinds.
     fn handle_contact_events(&mut self) {
-        let mut bullets_to_remove: Vec<Uuid> = vec![];
-        let mut babies_to_remove: Vec<Uuid> = vec![];
+        let mut contacts = vec![];
         for contact_event in self.physics_world.geometric_world().contact_events() {
             match contact_event {
                 ContactEvent::Started(first_handle, second_handle) => {
-                    let first_body_option = self.physics_world.body_set().rigid_body(*first_handle);
-                    let second_body_option =
-                        self.physics_world.body_set().rigid_body(*second_handle);
-
-                    if let (Some(first_body), Some(second_body)) =
-                        (first_body_option, second_body_option)
-                    {
-                        if let (Some(first_data), Some(second_data)) =
-                            (first_body.user_data(), second_body.user_data())
-                        {
-                            let (first_bullet, first_baby) = (
-                                first_data.downcast_ref::<BulletUserData>(),
-                                first_data.downcast_ref::<BabyUserData>(),
-                            );
-                            let (second_bullet, second_baby) = (
-                                second_data.downcast_ref::<BulletUserData>(),
-                                second_data.downcast_ref::<BabyUserData>(),
-                            );
-                            match (first_bullet, first_baby, second_bullet, second_baby) {
-                                (Some(bullet), None, None, Some(baby))
-                                | (None, Some(baby), Some(bullet), None) => {
-                                    bullets_to_remove.insert(0, bullet.uuid);
-                                    babies_to_remove.insert(0, baby.uuid);
-                                }
-                                (_, _, _, _) => {}
-                            }
-                        }
+                    contacts.push((ContactPhase::Started, *first_handle, *second_handle))
+                }
+                ContactEvent::Stopped(first_handle, second_handle) => {
+                    contacts.push((ContactPhase::Stopped, *first_handle, *second_handle))
+                }
+            }
+        }
+        // Sensors report proximity instead of contacts, only the moment they start overlapping matters
+        for proximity_event in self.physics_world.geometric_world().proximity_events() {
+            if proximity_event.new_status == Proximity::Intersecting
+                && proximity_event.prev_status != Proximity::Intersecting
+            {
+                contacts.push((
+                    ContactPhase::Sensed,
+                    proximity_event.collider1,
+                    proximity_event.collider2,
+                ));
+            }
+        }
+
+        for (phase, first_handle, second_handle) in contacts {
+            // Entities are looked up here rather than up front since an earlier handler may have removed one of them
+            if let (Some(first), Some(second)) = (
+                self.entities.by_collider(first_handle),
+                self.entities.by_collider(second_handle),
+            ) {
+                if let Some((handler, first, second)) =
+                    self.collision_handlers.get(phase, first, second)
+                {
+                    handler(self, first, second);
+                }
+            }
+        }
+    }
+
+    fn bullet_hit_enemy(&mut self, bullet: EntityId, enemy: EntityId) {
+        let (damage, handle) = match self.bullets.get(&bullet.uuid) {
+            Some(inserted_bullet) => (inserted_bullet.damage(), inserted_bullet.get_body_handle()),
+            None => return,
+        };
+        // The spray flies back out of the enemy towards where the bullet came from
+        if let Some((position, velocity)) = self.body_motion(handle) {
+            self.particles
+                .emit(ParticleEvent::Hit, position, Some(-velocity));
+        }
+        self.despawn(bullet);
+        self.sounds.push(SoundEffect::Hit);
+        self.score.hit();
+        self.damage(enemy, damage);
+    }
+
+    fn bullet_hit_wall(&mut self, bullet: EntityId, _wall: EntityId) {
+        self.despawn(bullet);
+    }
+
+    fn enemy_touched_player(&mut self, _player: EntityId, enemy: EntityId) {
+        self.enemies_touching_player.insert(enemy);
+    }
+
+    fn enemy_left_player(&mut self, _player: EntityId, enemy: EntityId) {
+        self.enemies_touching_player.remove(&enemy);
+    }
+
+    fn player_collected_pickup(&mut self, _player: EntityId, pickup: EntityId) {
+        let kind = match self.pickups.get(&pickup.uuid) {
+            Some(inserted_pickup) => inserted_pickup.kind(),
+            None => return,
+        };
+        self.character.apply_pickup(kind, &self.pickup_config);
+        self.sounds.push(SoundEffect::Pickup);
+        self.despawn(pickup);
+    }
+
+    /// Hurts the player if any enemies are touching them. The player's invulnerability window limits how often this lands.
+    fn apply_contact_damage(&mut self) {
+        if !self.enemies_touching_player.is_empty() {
+            self.damage(self.player_id, self.contact_damage);
+        }
+    }
+
+    /// Deals damage to an entity, queueing up a DeathEvent if it killed the entity.
+    fn damage(&mut self, id: EntityId, damage: Damage) {
+        let killed = match id.kind {
+            // The player's health goes through Character::hurt so invulnerability is respected
+            EntityKind::Player => {
+                if !self.character.is_invulnerable() {
+                    self.character
+                        .inserted_mut()
+                        .play_animation(AnimationState::Hit);
+                    self.sounds.push(SoundEffect::PlayerHurt);
+                }
+                self.character.hurt(damage)
+            }
+            // Enemies go through the Enemy trait so archetypes can change how damage lands
+            EntityKind::Enemy => self.enemies.get_mut(&id.uuid).map_or(false, |enemy| {
+                enemy
+                    .body_mut()
+                    .inserted_mut()
+                    .play_animation(AnimationState::Hit);
+                enemy.take_damage(damage)
+            }),
+            EntityKind::Bullet | EntityKind::Pickup | EntityKind::Wall => false,
+        };
+        if killed {
+            let position = self.entity_position(id);
+            self.deaths.push(DeathEvent { id, position });
+        }
+    }
+
+    /// Where a body is and how fast it is moving, if it is still in the world.
+    fn body_motion(&self, handle: DefaultBodyHandle) -> Option<(Vector2<f64>, Vector2<f64>)> {
+        self.physics_world
+            .body_set()
+            .rigid_body(handle)
+            .map(|rigid_body| {
+                (
+                    rigid_body.position().translation.vector,
+                    rigid_body.velocity().linear,
+                )
+            })
+    }
+
+    fn entity_position(&self, id: EntityId) -> Vector2<f64> {
+        let body_handle = match id.kind {
+            EntityKind::Player => Some(self.character.inserted().get_body_handle()),
+            EntityKind::Enemy => self
+                .enemies
+                .get(&id.uuid)
+                .map(|enemy| enemy.body().get_body_handle()),
+            EntityKind::Bullet => self
+                .bullets
+                .get(&id.uuid)
+                .map(|bullet| bullet.get_body_handle()),
+            EntityKind::Pickup => self
+                .pickups
+                .get(&id.uuid)
+                .map(|pickup| pickup.get_body_handle()),
+            EntityKind::Wall => None,
+        };
+        body_handle
+            .and_then(|handle| self.physics_world.body_set().rigid_body(handle))
+            .map(|rigid_body| rigid_body.position().translation.vector)
+            .unwrap_or_else(Vector2::zeros)
+    }
+
+    /// Removes everything that died during this update.
+    /// Taking down the boss drives off every other enemy, which completes the wave. That only happens once
+    /// every other death in the update has been handled, so enemies killed alongside the boss still count.
+    fn handle_deaths(&mut self) {
+        let deaths = std::mem::take(&mut self.deaths);
+        let mut boss_defeated = false;
+        for death in deaths {
+            match death.id.kind {
+                EntityKind::Player => self.game_over = true,
+                EntityKind::Enemy => {
+                    if self.handle_enemy_death(death) == Some(EnemyType::Boss) {
+                        boss_defeated = true;
                     }
+                    self.despawn_dead_enemy(death.id);
+                }
+                EntityKind::Bullet | EntityKind::Pickup | EntityKind::Wall => {
+                    self.despawn(death.id)
                 }
-                ContactEvent::Stopped(_first_handle, _second_handle) => {}
             }
         }
-        for bullet_to_remove in bullets_to_remove {
-            if let Some(bullet_removed) = self.bullets.remove(&bullet_to_remove) {
+        if boss_defeated {
+            self.despawn_enemies();
+        }
+    }
+
+    /// Despawns an enemy that has been killed. If its sprite has a die clip the sprite is left behind as a corpse to play it.
+    fn despawn_dead_enemy(&mut self, id: EntityId) {
+        let mut enemy = match self.enemies.remove(&id.uuid) {
+            Some(enemy) => enemy,
+            None => return,
+        };
+        self.enemies_touching_player.remove(&id);
+        let inserted = enemy.body_mut().inserted_mut();
+        let animator = inserted
+            .take_animator()
+            .filter(|animator| animator.has_clip(AnimationState::Die));
+        match (inserted.get_sprite_uuid(), animator) {
+            (Some(sprite_uuid), Some(mut animator)) => {
+                animator.play(AnimationState::Die);
+                self.entities.unregister(inserted);
                 self.physics_world
-                    .body_set_mut()
-                    .remove(bullet_removed.get_body_handle());
-                self.scene.remove_child(bullet_removed.get_sprite_uuid());
+                    .remove(inserted.get_body_handle(), inserted.get_collider_handle());
+                self.corpses.push((sprite_uuid, animator));
             }
+            _ => self.remove_inserted(inserted),
         }
-        for baby_to_remove in babies_to_remove {
-            if let Some(baby_removed) = self.babies.remove(&baby_to_remove) {
-                self.physics_world
-                    .body_set_mut()
-                    .remove(baby_removed.get_body_handle());
-                self.scene.remove_child(baby_removed.get_sprite_uuid());
+    }
+
+    /// Scores the kill, rolls for a drop and spawns whatever the enemy leaves behind, spread out in a circle.
+    /// Returns the type of the enemy that died, or None if it was already gone.
+    fn handle_enemy_death(&mut self, death: DeathEvent) -> Option<EnemyType> {
+        let (enemy_type, left_behind) = match self.enemies.get(&death.id.uuid) {
+            Some(enemy) => (enemy.enemy_type(), enemy.on_death()),
+            None => return None,
+        };
+        self.score.kill(enemy_type);
+        if enemy_type == EnemyType::Baby {
+            self.sounds.push(SoundEffect::BabyCry);
+        }
+        self.particles
+            .emit(ParticleEvent::Death, death.position, None);
+        self.roll_drop(death.position);
+        self.spawn_around(death.position, left_behind, LEFT_BEHIND_SPREAD);
+        Some(enemy_type)
+    }
+
+    fn despawn_enemies(&mut self) {
+        let enemies: Vec<Uuid> = self.enemies.keys().cloned().collect();
+        for uuid in enemies {
+            self.despawn(EntityId::new(EntityKind::Enemy, uuid));
+        }
+    }
+
+    /// Maybe drops a pickup where an enemy died, picked from the drop table by weight.
+    fn roll_drop(&mut self, position: Vector2<f64>) {
+        let mut rng = rand::thread_rng();
+        if rng.gen::<f64>() >= self.pickup_config.drop_chance {
+            return;
+        }
+        let kind = match self
+            .pickup_config
+            .drop_table
+            .choose_weighted(&mut rng, |drop| drop.weight)
+        {
+            Ok(drop) => drop.pickup,
+            // An empty table or one with only zero weights never drops anything
+            Err(_) => return,
+        };
+        self.spawn_pickup(kind, position);
+    }
+
+    fn spawn_pickup(&mut self, kind: PickupKind, position: Vector2<f64>) {
+        let (pickup_insertable, pickup_data) = PickupUserData::generate_insertable(kind, position);
+        let inserted = self.insert_insertable(pickup_insertable);
+        self.entities.register(
+            EntityId::new(EntityKind::Pickup, pickup_data.uuid),
+            &inserted,
+        );
+        self.pickups.insert(
+            pickup_data.uuid,
+            InsertedPickup::new(inserted, &pickup_data, self.pickup_config.lifetime),
+        );
+    }
+
+    /// Removes an entity from the world along with its physics objects and sprite.
+    fn despawn(&mut self, id: EntityId) {
+        self.enemies_touching_player.remove(&id);
+        match id.kind {
+            EntityKind::Enemy => {
+                if let Some(enemy) = self.enemies.remove(&id.uuid) {
+                    self.remove_inserted(enemy.body().inserted());
+                }
             }
+            EntityKind::Bullet => {
+                if let Some(bullet) = self.bullets.remove(&id.uuid) {
+                    self.remove_inserted(bullet.inserted());
+                }
+            }
+            EntityKind::Pickup => {
+                if let Some(pickup) = self.pickups.remove(&id.uuid) {
+                    self.remove_inserted(pickup.inserted());
+                }
+            }
+            // The player and the level's walls are never despawned
+            EntityKind::Player | EntityKind::Wall => {}
+        }
+    }
+
+    fn remove_inserted(&mut self, inserted: &Inserted) {
+        self.entities.unregister(inserted);
+        self.physics_world
+            .remove(inserted.get_body_handle(), inserted.get_collider_handle());
+        if let Some(sprite_uuid) = inserted.get_sprite_uuid() {
+            self.scene.remove_child(sprite_uuid);
         }
     }
 
-    /// Render the game. Currently we just need to clear the stencil and let the sprite world draw.
-    pub fn render(&self, _context: Context, transform: Matrix2d, graphics: &mut GlGraphics) {
+    /// Render the game. The level's tiles are drawn first, then the sprite world and the particles on top of them, all through the camera.
+    /// The camera picks up the size of the view here, so it follows the window when it is resized.
+    pub fn render(&mut self, context: Context, transform: Matrix2d, graphics: &mut GlGraphics) {
         clear([0.8, 0.8, 0.8, 1.0], graphics);
         graphics.clear_stencil(0);
+        self.camera.set_viewport(context.get_view_size());
+        let transform = self.camera.transform(transform);
+        self.tiles.render(transform, graphics);
         self.scene.draw(transform, graphics);
+        self.particles.render(transform, graphics);
+    }
+
+    /// Forgets every key and mouse button that is being held down.
+    pub fn clear_input(&mut self) {
+        self.keys_pressed.clear();
+        self.mouse_buttons_pressed.clear();
     }
 
     pub fn handle_mouse(&mut self, motion: Motion) {
-        if let Motion::MouseCursor(motion) = motion {
-            let body_set = self.physics_world.body_set_mut();
-            self.mouse_position = motion;
-            self.character.update_rotation(motion, body_set);
+        match motion {
+            Motion::MouseCursor(motion) => {
+                let body_set = self.physics_world.body_set_mut();
+                self.mouse_position = motion;
+                self.character
+                    .update_rotation(self.camera.screen_to_world(motion), body_set);
+            }
+            // Scrolling down moves to the next weapon and scrolling up to the previous one
+            Motion::MouseScroll([_, scroll_y]) => {
+                if scroll_y < 0.0 {
+                    self.character.cycle_weapon(1);
+                } else if scroll_y > 0.0 {
+                    self.character.cycle_weapon(-1);
+                }
+            }
+            _ => {}
         }
     }
 
@@ -199,42 +955,105 @@ impl World {
             ButtonState::Press => match key.button {
                 Button::Keyboard(key) => {
                     self.keys_pressed.insert(key);
+                    self.handle_weapon_key(key);
                 }
                 Button::Mouse(mouse_button) => {
+                    self.mouse_buttons_pressed.insert(mouse_button);
                     if let MouseButton::Left = mouse_button {
-                        self.left_mouse_click();
+                        self.fire_weapon();
                     }
                 }
                 _ => {}
             },
-            ButtonState::Release => {
-                if let Button::Keyboard(key) = key.button {
+            ButtonState::Release => match key.button {
+                Button::Keyboard(key) => {
                     self.keys_pressed.remove(&key);
                 }
-            }
+                Button::Mouse(mouse_button) => {
+                    self.mouse_buttons_pressed.remove(&mouse_button);
+                }
+                _ => {}
+            },
         }
     }
 
+    /// The number keys pick a weapon and R reloads the current one.
+    fn handle_weapon_key(&mut self, key: Key) {
+        let weapon_index = match key {
+            Key::D1 => 0,
+            Key::D2 => 1,
+            Key::D3 => 2,
+            Key::D4 => 3,
+            Key::D5 => 4,
+            Key::D6 => 5,
+            Key::D7 => 6,
+            Key::D8 => 7,
+            Key::D9 => 8,
+            Key::R => {
+                if let Some(weapon) = self.character.weapon_mut() {
+                    weapon.reload();
+                }
+                return;
+            }
+            _ => return,
+        };
+        self.character.select_weapon(weapon_index);
+    }
+
     pub fn insert_insertable(&mut self, to_insert: Insertable) -> Inserted {
-        let (sprite_tex, physics_insertable) = to_insert.get_parts_insertable();
-        let id = self.insert_sprite(sprite_tex);
+        let mut inserted = World::insert_physics(&mut self.physics_world, to_insert);
+        if let Some(assets) = self.assets.as_mut() {
+            // A missing texture shouldn't take the game down, the object just won't be drawn
+            let texture_name = inserted.get_texture_name();
+            if !self.failed_textures.contains(texture_name) {
+                if let Err(error) = World::attach_sprite(&mut self.scene, assets, &mut inserted) {
+                    self.failed_textures.insert(texture_name);
+                    self.errors.push(WorldError::Sprite(error));
+                }
+            }
+        }
+        inserted
+    }
 
-        let physics_inserted = self.physics_world.insert(physics_insertable);
-        Inserted::new_from_physics(id, physics_inserted)
+    fn insert_physics(physics_world: &mut PhysicsWorld, to_insert: Insertable) -> Inserted {
+        let (texture_name, physics_insertable) = to_insert.get_parts_insertable();
+        let physics_inserted = physics_world.insert(physics_insertable);
+        Inserted::new_from_physics(texture_name, physics_inserted)
     }
 
-    fn left_mouse_click(&mut self) {
+    /// Fires the current weapon, spawning a bullet for each projectile in the shot.
+    fn fire_weapon(&mut self) {
         let body_set = self.physics_world.body_set();
         let player_position = self.character.get_position(body_set);
         let player_rotation = self.character.get_rotation(body_set);
-        let (bullet, bullet_uuid) =
-            BulletUserData::generate_insertable(player_position, player_rotation);
-        let inserted_bullet = self.insert_insertable(bullet);
-        self.bullets
-            .insert(bullet_uuid, InsertedBullet::new(inserted_bullet));
+        let weapon = match self.character.weapon_mut() {
+            Some(weapon) => weapon,
+            None => return,
+        };
+        let (speed, damage) = (weapon.projectile_speed(), weapon.damage());
+        if let Some(rotations) = weapon.fire(player_rotation) {
+            self.sounds.push(SoundEffect::Shoot);
+            for rotation in rotations {
+                self.spawn_bullet(player_position, rotation, speed, damage);
+            }
+        }
     }
 
-    fn insert_sprite(&mut self, sprite_tex: Rc<Texture>) -> Uuid {
-        self.scene.add_child(Sprite::from_texture(sprite_tex))
+    fn spawn_bullet(&mut self, position: Vector2<f64>, rotation: f64, speed: f64, damage: u32) {
+        let (bullet, bullet_data) =
+            BulletUserData::generate_insertable(position, rotation, speed, damage, &self.hitboxes);
+        let inserted_bullet = self.insert_insertable(bullet);
+        if let Some((muzzle, velocity)) = self.body_motion(inserted_bullet.get_body_handle()) {
+            self.particles
+                .emit(ParticleEvent::Fired, muzzle, Some(velocity));
+        }
+        self.entities.register(
+            EntityId::new(EntityKind::Bullet, bullet_data.uuid),
+            &inserted_bullet,
+        );
+        self.bullets.insert(
+            bullet_data.uuid,
+            InsertedBullet::new(inserted_bullet, &bullet_data, &self.projectiles),
+        );
     }
 }
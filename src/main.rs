@@ -2,8 +2,10 @@ extern crate graphics;
 extern crate piston_window;
 extern crate vecmath;
 
+#[cfg(feature = "sound")]
+use zombies::game::audio::rodio_backend::RodioBackend;
+use zombies::game::state::Game;
 use zombies::game::view::View;
-use zombies::game::world::World;
 
 use input::RenderEvent;
 use piston_window::{Event, EventSettings, Events, Input, Loop};
@@ -11,32 +13,29 @@ use zombies::config::settings::Settings;
 
 fn main() {
     let config = Settings::new().unwrap();
-    // Currently this needs to be instantiated before the world
-    //      because the open_gl initiation occurs in it
     let mut game_view = View::new(&config);
-    let mut game_world = World::new(&config);
+    let mut game = Game::new(config).unwrap();
+    // Textures can only be loaded once the view has initialized open_gl
+    game.load_graphics().unwrap();
+    install_sound(&mut game);
     // TODO: May eventually want to have these event settings as config options
     let mut events = Events::new(EventSettings::new());
 
     while let Some(event) = events.next(&mut game_view.window) {
         match event {
             Event::Input(input_event, _timestamp) => match input_event {
-                Input::Button(key) => game_world.handle_button_event(key),
-                Input::Move(motion) => game_world.handle_mouse(motion),
+                Input::Button(key) => game.handle_button_event(key),
+                Input::Move(motion) => game.handle_mouse(motion),
                 _ => {}
             },
             Event::Loop(loop_event) => match loop_event {
-                Loop::Update(_) => {
-                    game_world.update();
-                    game_world.step();
-                }
+                Loop::Update(args) => game.update(args.dt),
                 Loop::Render(_) => {
                     if let Some(args) = event.render_args() {
                         game_view
                             .gl_graphics
                             .draw(args.viewport(), |context, graphics| {
-                                let transform = context.transform;
-                                game_world.render(context, transform, graphics);
+                                game.render(context, graphics);
                             })
                     }
                     //                    game_view.window.draw_2d(&event, |context, graphics, _| {
@@ -47,5 +46,21 @@ fn main() {
             },
             _ => {}
         }
+        if game.should_quit() {
+            break;
+        }
+    }
+}
+
+// Without a sound device the game carries on silently
+#[cfg(feature = "sound")]
+fn install_sound(game: &mut Game) {
+    match RodioBackend::new() {
+        Ok(backend) => game.set_audio_backend(Box::new(backend)),
+        Err(error) => println!("Playing without sound: {}", error),
     }
 }
+
+// Built without the sound feature the game keeps its silent backend
+#[cfg(not(feature = "sound"))]
+fn install_sound(_game: &mut Game) {}
@@ -0,0 +1,39 @@
+use zombies::config::settings::Settings;
+use zombies::game::animation::hitbox::Hitboxes;
+use zombies::game::level::Level;
+use zombies::game::world::World;
+
+// Steps at 60 frames per second, the same as the window
+const FRAME: f64 = 1.0 / 60.0;
+
+// Builds the world from the default config and level without opening a window or loading any textures
+fn world(config: &Settings) -> World {
+    let level = Level::load(&config.level.map).unwrap();
+    let hitboxes = Hitboxes::load().unwrap();
+    World::new(config, &level, &hitboxes).unwrap()
+}
+
+#[test]
+fn the_world_runs_without_graphics() {
+    let mut world = world(&Settings::new().unwrap());
+    // Two seconds, the first enemies are spawned but can't have worn the player down yet
+    for _ in 0..120 {
+        world.advance(FRAME);
+    }
+    assert!(!world.is_game_over());
+    assert_eq!(world.current_wave(), 1);
+    let alpha = world.interpolation_alpha();
+    assert!(alpha >= 0.0 && alpha < 1.0);
+}
+
+#[test]
+fn a_slow_frame_does_not_leave_the_world_behind() {
+    let config = Settings::new().unwrap();
+    let timestep = config.physics.timestep;
+    let mut world = world(&config);
+    // Far more time than max_substeps can catch up on, with half a step over
+    world.advance(timestep * (f64::from(config.physics.max_substeps) + 15.5));
+    assert_eq!(world.steps(), u64::from(config.physics.max_substeps));
+    // Only the half step is carried over, the rest is dropped
+    assert!((world.interpolation_alpha() - 0.5).abs() < 1.0e-6);
+}